@@ -0,0 +1,48 @@
+//! Hemera is a wallpaper engine built on wgpu. This crate contains only the rendering
+//! engine; windowing, the daemon and IPC live in the embedding application.
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use hemera::{Engine, Manifest};
+//!
+//! let manifest = Manifest::from_path("wallpaper.toml")?;
+//! let mut engine = Engine::new_headless(1920, 1080).await?;
+//! engine.load_scene(&manifest)?;
+//!
+//! engine.update();
+//! engine.render()?;
+//! engine.read_frame()?.save("frame.png")?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod manifest;
+pub(crate) mod renderer;
+
+pub use manifest::Manifest;
+pub use renderer::{
+    animated::Playback,
+    animation::{Animation, Keyframe, Repeat},
+    clock::{Clock, ManualClock, RealTimeClock},
+    effects::{EffectKind, EffectSettings},
+    engine::{Engine, EngineEvent},
+    image_effects::{ImageEffectKind, ImageEffectSettings},
+    primitives::{image::BlendMode, plane::FitMode},
+    scenes::{
+        gif_scene::GifScene,
+        image_scene::ImageScene,
+        parallax_scene::ParallaxScene,
+        scene::{Scene, SceneType},
+        schedule_scene::ScheduleScene,
+        slideshow_scene::SlideshowScene,
+        test_image_scene::TestImageScene,
+        video_scene::VideoScene,
+    },
+    schedule::TimeOfDay,
+    timeline::Easing,
+    transitions::{
+        Direction, InterruptPolicy, Transition, TransitionKind, TransitionName, TransitionSettings,
+        TransitionShader, TransitionSpec,
+    },
+    validation::validate_manifest,
+};
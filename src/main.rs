@@ -1,8 +1,122 @@
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::Context;
+use chrono::{DateTime, FixedOffset, Local};
+use clap::{Parser, Subcommand};
+use hemera::{validate_manifest, Engine, Manifest, ManualClock};
 use window::run;
 
-mod renderer;
 mod window;
 
-fn main() {
-    pollster::block_on(run());
+#[derive(Parser)]
+#[command(name = "hemera", version, about = "Animated wallpaper engine")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Open a window showing the manifests. Space switches to the next one.
+    Play {
+        #[arg(required = true)]
+        manifests: Vec<PathBuf>,
+    },
+    /// Render frames offscreen and write them out as PNG files.
+    Render {
+        manifest: PathBuf,
+        /// Number of frames to render.
+        #[arg(long, default_value_t = 60)]
+        frames: u32,
+        /// Directory the frames are written to. Created if missing.
+        #[arg(long)]
+        out: PathBuf,
+        #[arg(long, default_value_t = 1920)]
+        width: u32,
+        #[arg(long, default_value_t = 1080)]
+        height: u32,
+        /// Frames per second of the output.
+        #[arg(long, default_value_t = 30)]
+        fps: u32,
+        /// Local time of the first frame, for scheduled scenes, e.g.
+        /// `2024-06-21T18:30:00+02:00`. Defaults to now.
+        #[arg(long)]
+        at: Option<DateTime<FixedOffset>>,
+    },
+    /// Check manifests, their assets and shaders without opening a window.
+    Validate {
+        #[arg(required = true)]
+        manifests: Vec<PathBuf>,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Play { manifests } => {
+            let manifests = manifests
+                .iter()
+                .map(Manifest::from_path)
+                .collect::<anyhow::Result<Vec<Manifest>>>()?;
+
+            pollster::block_on(run(manifests))
+        }
+        Command::Render {
+            manifest,
+            frames,
+            out,
+            width,
+            height,
+            fps,
+            at,
+        } => {
+            let manifest = Manifest::from_path(manifest)?;
+            let at = at.unwrap_or_else(|| Local::now().fixed_offset());
+            pollster::block_on(render(&manifest, frames, &out, width, height, fps, at))
+        }
+        Command::Validate { manifests } => {
+            for path in manifests {
+                Manifest::from_path(&path)
+                    .and_then(|manifest| validate_manifest(&manifest))
+                    .with_context(|| format!("`{}` is invalid", path.display()))?;
+                println!("{}: ok", path.display());
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn render(
+    manifest: &Manifest,
+    frames: u32,
+    out: &std::path::Path,
+    width: u32,
+    height: u32,
+    fps: u32,
+    at: DateTime<FixedOffset>,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(out)
+        .with_context(|| format!("failed to create `{}`", out.display()))?;
+
+    // Frames are rendered at exact timestamps, as fast as the GPU allows
+    let clock = ManualClock::starting_at(at);
+    let mut engine = Engine::new_headless(width, height).await?;
+    engine.set_clock(clock.clone());
+    engine.load_scene(manifest)?;
+
+    let frame_time = Duration::from_secs_f64(1.0 / fps.max(1) as f64);
+    for frame in 0..frames {
+        clock.set(frame_time * frame);
+        engine.update();
+        engine.render()?;
+
+        let path = out.join(format!("frame_{frame:05}.png"));
+        engine
+            .read_frame()?
+            .save(&path)
+            .with_context(|| format!("failed to write `{}`", path.display()))?;
+    }
+
+    Ok(())
 }
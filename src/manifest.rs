@@ -0,0 +1,726 @@
+use std::{
+    collections::BTreeMap,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use serde::{
+    de::{self, IgnoredAny, Visitor},
+    Deserialize, Deserializer,
+};
+
+use crate::renderer::{
+    animation::Animation,
+    effects::EffectSettings,
+    image_effects::ImageEffectSettings,
+    primitives::{image::BlendMode, plane::FitMode},
+    schedule::{self, TimeOfDay},
+    transitions::TransitionSettings,
+};
+
+/// Declarative description of a wallpaper, loaded from a TOML or JSON file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub scene: SceneManifest,
+    #[serde(default = "default_clear_color")]
+    pub clear_color: [f64; 4],
+    /// Transition used when switching *to* this scene. `None` switches instantly.
+    #[serde(default)]
+    pub transition: Option<TransitionSettings>,
+    /// Post-processing applied to every frame, in order.
+    #[serde(default)]
+    pub effects: Vec<EffectSettings>,
+}
+
+/// The scene variant is the single key of the `scene` table, e.g. `[scene.gif]`,
+/// `[scene.video]` or `[[scene.images]]`. Schedules nest scenes of their own.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SceneManifest {
+    Image(ImageManifest),
+    Images(Vec<ImageManifest>),
+    Gif(GifManifest),
+    Video(VideoManifest),
+    Slideshow(SlideshowManifest),
+    Schedule(ScheduleManifest),
+    Parallax(ParallaxManifest),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImageManifest {
+    pub path: PathBuf,
+    /// How the image is fitted to the output. Defaults to stretching it.
+    #[serde(default)]
+    pub fit: FitMode,
+    /// Either one number or `[x, y]`. Tiled images scale their tiles by x.
+    #[serde(default = "default_scale", deserialize_with = "deserialize_scale")]
+    pub scale: [f32; 2],
+    /// Offset in clip space, where the output spans -1..1 and y points up.
+    #[serde(default)]
+    pub offset: [f32; 2],
+    /// Clockwise rotation in degrees around `anchor`.
+    #[serde(default)]
+    pub rotation: f32,
+    /// Point of the image that rotation and scale keep in place, from `[0, 0]` at its top
+    /// left to `[1, 1]` at its bottom right.
+    #[serde(default = "default_anchor")]
+    pub anchor: [f32; 2],
+    /// Images with a higher index are drawn on top.
+    #[serde(default)]
+    pub z_index: i32,
+    /// From 0, invisible, to 1.
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    /// Multiplied with the image's colors.
+    #[serde(default = "default_tint")]
+    pub tint: [f32; 3],
+    /// How the image is combined with the images below it. Defaults to `alpha`.
+    #[serde(default)]
+    pub blend: BlendMode,
+    /// Keyframes overriding the values above over time.
+    #[serde(default)]
+    pub animation: Option<Animation>,
+    /// Shader effect drawing the image, e.g. `{ name = "waves", amplitude = 0.02 }`.
+    #[serde(default)]
+    pub effect: Option<ImageEffectSettings>,
+}
+
+/// An animated gif, png (APNG) or webp, placed like an image.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GifManifest {
+    #[serde(flatten)]
+    pub image: ImageManifest,
+    /// Megabytes of decoded frames kept in memory. Larger gifs are decoded while they play.
+    #[serde(default)]
+    pub memory_budget: Option<u32>,
+    /// Playback rate, 1.0 being normal speed.
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+    /// Play from the last frame to the first. Only for animations within `memory_budget`:
+    /// streamed ones can only play forwards, and [`validate_manifest`](crate::validate_manifest)
+    /// rejects them.
+    #[serde(default)]
+    pub reverse: bool,
+    /// Keys neither the gif nor the image know. Rejected by [`Manifest::validate`].
+    #[serde(flatten)]
+    unknown_fields: BTreeMap<String, IgnoredAny>,
+}
+
+/// A video decoded with `ffmpeg`, placed like an image.
+#[derive(Debug, Clone, Deserialize)]
+pub struct VideoManifest {
+    #[serde(flatten)]
+    pub image: ImageManifest,
+    /// Start over after the last frame. Defaults to true.
+    #[serde(default = "default_loop", rename = "loop")]
+    pub looping: bool,
+    /// Playback rate, 1.0 being normal speed.
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+    /// Seconds into the video where playback starts.
+    #[serde(default)]
+    pub start: f32,
+    /// Keys neither the video nor the image know, since `deny_unknown_fields` doesn't work
+    /// through `flatten`. Rejected by [`Manifest::validate`].
+    #[serde(flatten)]
+    unknown_fields: BTreeMap<String, IgnoredAny>,
+}
+
+/// Images and animations shown one after another.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SlideshowManifest {
+    /// Shows every image in this directory, sorted by file name. Either this or `paths`.
+    #[serde(default)]
+    pub directory: Option<PathBuf>,
+    #[serde(default)]
+    pub paths: Vec<PathBuf>,
+    /// Seconds each slide stays on screen, counted from the start of its transition.
+    pub interval: f32,
+    #[serde(default)]
+    pub order: SlideOrder,
+    /// How every slide is fitted to the output. Defaults to covering it.
+    #[serde(default = "default_slide_fit")]
+    pub fit: FitMode,
+    /// Transition between slides. `None` switches instantly.
+    #[serde(default)]
+    pub transition: Option<TransitionSettings>,
+}
+
+/// Scenes following the time of day, each shown from its entry's start until the next
+/// one's.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleManifest {
+    /// Degrees north, for entries starting at sunrise or sunset.
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    /// Degrees east, for entries starting at sunrise or sunset.
+    #[serde(default)]
+    pub longitude: Option<f64>,
+    pub entries: Vec<ScheduleEntry>,
+    /// Transition between entries. `None` switches instantly.
+    #[serde(default)]
+    pub transition: Option<TransitionSettings>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleEntry {
+    pub at: TimeOfDay,
+    /// Minutes added to `at`, e.g. -30 to start half an hour before sunset.
+    #[serde(default)]
+    pub offset: f32,
+    pub scene: SceneManifest,
+}
+
+/// Image layers shifting with the cursor by their depth, drifting on their own while the
+/// cursor is away.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParallaxManifest {
+    /// Drawn like `[[scene.images]]`, by `z_index` and then in order.
+    pub layers: Vec<ParallaxLayer>,
+    /// Seconds the layers take to catch up about two thirds of the way to the cursor, 0 to
+    /// follow it exactly.
+    #[serde(default = "default_smoothing")]
+    pub smoothing: f32,
+    /// Seconds without cursor movement before the idle drift takes over.
+    #[serde(default = "default_idle_after")]
+    pub idle_after: f32,
+    /// How far the drift strays from the center, as a fraction of the cursor's range. 0
+    /// keeps idle layers centered.
+    #[serde(default = "default_drift_radius")]
+    pub drift_radius: f32,
+    /// Seconds one loop of the drift takes.
+    #[serde(default = "default_drift_period")]
+    pub drift_period: f32,
+}
+
+/// An image of a parallax scene.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParallaxLayer {
+    #[serde(flatten)]
+    pub image: ImageManifest,
+    /// How far the layer shifts away from the cursor, in clip space when the cursor is at an
+    /// edge of the output. Near layers shift more than far ones, 0 keeps a layer still and
+    /// negative depths follow the cursor.
+    #[serde(default)]
+    pub depth: f32,
+    /// Keys neither the layer nor the image know. Rejected by [`Manifest::validate`].
+    #[serde(flatten)]
+    unknown_fields: BTreeMap<String, IgnoredAny>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlideOrder {
+    #[default]
+    Ordered,
+    /// A random order, reshuffled after every pass through all slides.
+    Shuffle,
+}
+
+fn default_clear_color() -> [f64; 4] {
+    [0.0, 0.5, 0.3, 1.0]
+}
+
+fn default_scale() -> [f32; 2] {
+    [1.0, 1.0]
+}
+
+fn default_smoothing() -> f32 {
+    0.25
+}
+
+fn default_idle_after() -> f32 {
+    5.0
+}
+
+fn default_drift_radius() -> f32 {
+    0.5
+}
+
+fn default_drift_period() -> f32 {
+    20.0
+}
+
+fn default_slide_fit() -> FitMode {
+    FitMode::Cover
+}
+
+fn default_anchor() -> [f32; 2] {
+    [0.5, 0.5]
+}
+
+fn default_loop() -> bool {
+    true
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+fn default_tint() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+/// Accepts `scale = 0.5` as well as `scale = [0.5, 1.0]`.
+pub(crate) fn deserialize_scale<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<[f32; 2], D::Error> {
+    struct ScaleVisitor;
+
+    impl<'de> Visitor<'de> for ScaleVisitor {
+        type Value = [f32; 2];
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a number or an array of two numbers")
+        }
+
+        fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+            Ok([v as f32; 2])
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            Ok([v as f32; 2])
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok([v as f32; 2])
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            <[f32; 2]>::deserialize(de::value::SeqAccessDeserializer::new(seq))
+        }
+    }
+
+    deserializer.deserialize_any(ScaleVisitor)
+}
+
+pub(crate) fn deserialize_optional_scale<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<[f32; 2]>, D::Error> {
+    deserialize_scale(deserializer).map(Some)
+}
+
+impl ImageManifest {
+    /// An image at `path` with every other key at its default.
+    pub fn new(path: PathBuf) -> Self {
+        ImageManifest {
+            path,
+            fit: FitMode::default(),
+            scale: default_scale(),
+            offset: [0.0, 0.0],
+            rotation: 0.0,
+            anchor: default_anchor(),
+            z_index: 0,
+            opacity: default_opacity(),
+            tint: default_tint(),
+            blend: BlendMode::default(),
+            animation: None,
+            effect: None,
+        }
+    }
+}
+
+impl GifManifest {
+    /// An animation placed as `image` says, with every other key at its default.
+    pub fn new(image: ImageManifest) -> Self {
+        GifManifest {
+            image,
+            memory_budget: None,
+            speed: default_speed(),
+            reverse: false,
+            unknown_fields: BTreeMap::new(),
+        }
+    }
+}
+
+impl SlideshowManifest {
+    /// The files to show, in manifest order: the listed paths or the directory's images.
+    pub fn slides(&self) -> anyhow::Result<Vec<PathBuf>> {
+        let Some(directory) = &self.directory else {
+            return Ok(self.paths.clone());
+        };
+
+        let entries = std::fs::read_dir(directory)
+            .with_context(|| format!("failed to read directory `{}`", directory.display()))?;
+        let mut slides = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            // Told apart by their signatures like when decoding, whatever the extensions say
+            if path.is_file() && is_image(&path)? {
+                slides.push(path);
+            }
+        }
+        slides.sort();
+        Ok(slides)
+    }
+
+    /// A slide at `path`, fitted as the slideshow says.
+    pub fn slide(&self, path: PathBuf) -> ImageManifest {
+        ImageManifest {
+            fit: self.fit,
+            ..ImageManifest::new(path)
+        }
+    }
+}
+
+/// Whether `path` starts with the signature of a format `image` decodes.
+fn is_image(path: &Path) -> anyhow::Result<bool> {
+    let mut header = Vec::with_capacity(16);
+    std::fs::File::open(path)
+        .and_then(|file| file.take(16).read_to_end(&mut header))
+        .with_context(|| format!("failed to read `{}`", path.display()))?;
+    Ok(image::guess_format(&header).is_ok())
+}
+
+impl ScheduleManifest {
+    /// `(latitude, longitude)` if both are set.
+    pub fn location(&self) -> Option<(f64, f64)> {
+        self.latitude.zip(self.longitude)
+    }
+
+    /// Index of the entry whose start most recently passed at `now`, wrapping around to the
+    /// previous day before the first one. `None` if no entry can start around `now`, e.g. only
+    /// sunrises during polar night.
+    pub fn active_entry(&self, now: chrono::DateTime<chrono::FixedOffset>) -> Option<usize> {
+        let entries = self.entries.iter().map(|entry| {
+            let offset = chrono::Duration::milliseconds((entry.offset * 60_000.0) as i64);
+            (entry.at, offset)
+        });
+        schedule::active_entry(entries, now, self.location())
+    }
+}
+
+impl Manifest {
+    /// Reads and validates a manifest. The format is picked from the file extension
+    /// (`.json` for JSON, anything else is parsed as TOML) and relative asset paths are
+    /// resolved against the manifest's directory.
+    pub fn from_path(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read manifest `{}`", path.display()))?;
+
+        let is_json = path.extension().is_some_and(|ext| ext == "json");
+        let mut manifest = if is_json {
+            Self::from_json(&source)
+        } else {
+            Self::from_toml(&source)
+        }
+        .with_context(|| format!("invalid manifest `{}`", path.display()))?;
+
+        if let Some(base) = path.parent() {
+            manifest.resolve_paths(base);
+        }
+        manifest
+            .validate()
+            .with_context(|| format!("invalid manifest `{}`", path.display()))?;
+
+        Ok(manifest)
+    }
+
+    pub fn from_toml(source: &str) -> anyhow::Result<Self> {
+        let deserializer = toml::Deserializer::new(source);
+        serde_path_to_error::deserialize(deserializer).map_err(|err| {
+            let key = err.path().to_string();
+            anyhow::anyhow!("`{}`: {}", key, err.into_inner().message().trim())
+        })
+    }
+
+    pub fn from_json(source: &str) -> anyhow::Result<Self> {
+        let mut deserializer = serde_json::Deserializer::from_str(source);
+        serde_path_to_error::deserialize(&mut deserializer).map_err(|err| {
+            let key = err.path().to_string();
+            anyhow::anyhow!("`{}`: {}", key, err.into_inner())
+        })
+    }
+
+    fn resolve_paths(&mut self, base: &Path) {
+        if let Some(shader) = self.transition.as_mut().and_then(|t| t.shader.as_mut()) {
+            if shader.is_relative() {
+                *shader = base.join(&shader);
+            }
+        }
+        self.scene.resolve_paths(base);
+    }
+
+    /// Checks values serde can't: value ranges and that referenced assets exist.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.clear_color.iter().any(|c| !(0.0..=1.0).contains(c)) {
+            bail!("`clear_color`: components must be between 0.0 and 1.0");
+        }
+
+        if let Some(transition) = &self.transition {
+            transition.validate("transition")?;
+        }
+        for (i, effect) in self.effects.iter().enumerate() {
+            effect.validate(&format!("effects[{i}]"))?;
+        }
+
+        self.scene.validate("scene")
+    }
+}
+
+impl SceneManifest {
+    /// Checks values serde can't, reporting errors under `key`, where the scene sits in the
+    /// manifest.
+    pub fn validate(&self, key: &str) -> anyhow::Result<()> {
+        match self {
+            SceneManifest::Images(images) if images.is_empty() => {
+                bail!("`{key}.images`: at least one image is required");
+            }
+            SceneManifest::Gif(gif) => {
+                if let Some(field) = gif.unknown_fields.keys().next() {
+                    bail!("`{key}.gif.{field}`: unknown field");
+                }
+                if !gif.speed.is_finite() || gif.speed <= 0.0 {
+                    bail!("`{key}.gif.speed`: must be greater than 0");
+                }
+            }
+            SceneManifest::Slideshow(slideshow) => {
+                match (&slideshow.directory, slideshow.paths.is_empty()) {
+                    (Some(directory), true) => {
+                        if !directory.is_dir() {
+                            bail!(
+                                "`{key}.slideshow.directory`: `{}` is not a directory",
+                                directory.display()
+                            );
+                        }
+                        if slideshow.slides()?.is_empty() {
+                            bail!(
+                                "`{key}.slideshow.directory`: `{}` contains no images",
+                                directory.display()
+                            );
+                        }
+                    }
+                    (None, false) => {
+                        for (i, path) in slideshow.paths.iter().enumerate() {
+                            if !path.is_file() {
+                                bail!(
+                                    "`{key}.slideshow.paths[{i}]`: file `{}` does not exist",
+                                    path.display()
+                                );
+                            }
+                        }
+                    }
+                    _ => bail!(
+                        "`{key}.slideshow`: exactly one of `directory` and `paths` must be set"
+                    ),
+                }
+                if !slideshow.interval.is_finite() || slideshow.interval <= 0.0 {
+                    bail!("`{key}.slideshow.interval`: must be a positive number of seconds");
+                }
+                if let Some(transition) = &slideshow.transition {
+                    transition.validate(&format!("{key}.slideshow.transition"))?;
+                }
+            }
+            SceneManifest::Video(video) => {
+                if let Some(field) = video.unknown_fields.keys().next() {
+                    bail!("`{key}.video.{field}`: unknown field");
+                }
+                if !video.speed.is_finite() || video.speed <= 0.0 {
+                    bail!("`{key}.video.speed`: must be greater than 0");
+                }
+                if !video.start.is_finite() || video.start < 0.0 {
+                    bail!("`{key}.video.start`: must be a non-negative number of seconds");
+                }
+            }
+            SceneManifest::Schedule(schedule) => {
+                if schedule.entries.is_empty() {
+                    bail!("`{key}.schedule.entries`: at least one entry is required");
+                }
+                if let Some(latitude) = schedule.latitude {
+                    if !(-90.0..=90.0).contains(&latitude) {
+                        bail!("`{key}.schedule.latitude`: must be between -90 and 90 degrees");
+                    }
+                }
+                if let Some(longitude) = schedule.longitude {
+                    if !(-180.0..=180.0).contains(&longitude) {
+                        bail!("`{key}.schedule.longitude`: must be between -180 and 180 degrees");
+                    }
+                }
+                for (i, entry) in schedule.entries.iter().enumerate() {
+                    let entry_key = format!("{key}.schedule.entries[{i}]");
+                    if entry.at.is_solar() && schedule.location().is_none() {
+                        bail!(
+                            "`{entry_key}.at`: sunrise and sunset need `{key}.schedule.latitude` and `{key}.schedule.longitude`"
+                        );
+                    }
+                    if !entry.offset.is_finite() {
+                        bail!("`{entry_key}.offset`: must be a number of minutes");
+                    }
+                    entry.scene.validate(&format!("{entry_key}.scene"))?;
+                }
+                if let Some(transition) = &schedule.transition {
+                    transition.validate(&format!("{key}.schedule.transition"))?;
+                }
+            }
+            SceneManifest::Parallax(parallax) => {
+                if parallax.layers.is_empty() {
+                    bail!("`{key}.parallax.layers`: at least one layer is required");
+                }
+                for (i, layer) in parallax.layers.iter().enumerate() {
+                    let layer_key = format!("{key}.parallax.layers[{i}]");
+                    if let Some(field) = layer.unknown_fields.keys().next() {
+                        bail!("`{layer_key}.{field}`: unknown field");
+                    }
+                    if !layer.depth.is_finite() {
+                        bail!("`{layer_key}.depth`: must be a number");
+                    }
+                }
+                if !parallax.smoothing.is_finite() || parallax.smoothing < 0.0 {
+                    bail!("`{key}.parallax.smoothing`: must be a non-negative number of seconds");
+                }
+                if !parallax.idle_after.is_finite() || parallax.idle_after < 0.0 {
+                    bail!("`{key}.parallax.idle_after`: must be a non-negative number of seconds");
+                }
+                if !(0.0..=1.0).contains(&parallax.drift_radius) {
+                    bail!("`{key}.parallax.drift_radius`: must be between 0.0 and 1.0");
+                }
+                if !parallax.drift_period.is_finite() || parallax.drift_period <= 0.0 {
+                    bail!("`{key}.parallax.drift_period`: must be a positive number of seconds");
+                }
+            }
+            _ => {}
+        }
+
+        for (image_key, image) in self.images(key) {
+            if image.scale.iter().any(|s| !s.is_finite() || *s <= 0.0) {
+                bail!(
+                    "`{image_key}.scale`: must be greater than 0, got {:?}",
+                    image.scale
+                );
+            }
+            if !image.rotation.is_finite() {
+                bail!("`{image_key}.rotation`: must be a number of degrees");
+            }
+            if !(0.0..=1.0).contains(&image.opacity) {
+                bail!("`{image_key}.opacity`: must be between 0.0 and 1.0");
+            }
+            if image.tint.iter().any(|c| !(0.0..=1.0).contains(c)) {
+                bail!("`{image_key}.tint`: components must be between 0.0 and 1.0");
+            }
+            if let Some(animation) = &image.animation {
+                animation.validate(&format!("{image_key}.animation"))?;
+            }
+            if let Some(effect) = &image.effect {
+                effect.validate(&format!("{image_key}.effect"))?;
+            }
+            if !image.path.is_file() {
+                bail!(
+                    "`{image_key}.path`: file `{}` does not exist",
+                    image.path.display()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Every image referenced by the scene, paired with its manifest key under `key`, where
+    /// the scene sits in the manifest. Scenes nested in a schedule aren't included.
+    pub fn images(&self, key: &str) -> Vec<(String, &ImageManifest)> {
+        match self {
+            SceneManifest::Image(image) => vec![(format!("{key}.image"), image)],
+            SceneManifest::Gif(gif) => vec![(format!("{key}.gif"), &gif.image)],
+            SceneManifest::Video(video) => vec![(format!("{key}.video"), &video.image)],
+            // Slides are only known once the directory is read
+            SceneManifest::Slideshow(_) => vec![],
+            // Nested scenes report their own images
+            SceneManifest::Schedule(_) => vec![],
+            SceneManifest::Images(images) => images
+                .iter()
+                .enumerate()
+                .map(|(i, image)| (format!("{key}.images[{i}]"), image))
+                .collect(),
+            SceneManifest::Parallax(parallax) => parallax
+                .layers
+                .iter()
+                .enumerate()
+                .map(|(i, layer)| (format!("{key}.parallax.layers[{i}]"), &layer.image))
+                .collect(),
+        }
+    }
+
+    fn images_mut(&mut self) -> Vec<&mut ImageManifest> {
+        match self {
+            SceneManifest::Image(image) => vec![image],
+            SceneManifest::Gif(gif) => vec![&mut gif.image],
+            SceneManifest::Video(video) => vec![&mut video.image],
+            SceneManifest::Slideshow(_) | SceneManifest::Schedule(_) => vec![],
+            SceneManifest::Images(images) => images.iter_mut().collect(),
+            SceneManifest::Parallax(parallax) => parallax
+                .layers
+                .iter_mut()
+                .map(|layer| &mut layer.image)
+                .collect(),
+        }
+    }
+
+    fn resolve_paths(&mut self, base: &Path) {
+        for image in self.images_mut() {
+            if image.path.is_relative() {
+                image.path = base.join(&image.path);
+            }
+        }
+        match self {
+            SceneManifest::Slideshow(slideshow) => {
+                let shader = slideshow
+                    .transition
+                    .as_mut()
+                    .and_then(|t| t.shader.as_mut());
+                let paths = slideshow.directory.iter_mut().chain(&mut slideshow.paths);
+                for path in paths.chain(shader) {
+                    if path.is_relative() {
+                        *path = base.join(&path);
+                    }
+                }
+            }
+            SceneManifest::Schedule(schedule) => {
+                if let Some(shader) = schedule.transition.as_mut().and_then(|t| t.shader.as_mut()) {
+                    if shader.is_relative() {
+                        *shader = base.join(&shader);
+                    }
+                }
+                for entry in &mut schedule.entries {
+                    entry.scene.resolve_paths(base);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::animated::tests::gif;
+
+    #[test]
+    fn slides_are_recognized_by_contents() {
+        let directory = std::env::temp_dir().join(format!("hemera-slides-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("a.txt"), gif(2, 4, 100)).unwrap();
+        std::fs::write(directory.join("b"), gif(1, 4, 100)).unwrap();
+        std::fs::write(directory.join("c.png"), "not an image").unwrap();
+        std::fs::write(directory.join("d.gif"), "").unwrap();
+
+        let slideshow: SlideshowManifest =
+            toml::from_str(&format!("directory = {directory:?}\ninterval = 1.0")).unwrap();
+        let slides = slideshow.slides();
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(
+            slides.unwrap(),
+            [directory.join("a.txt"), directory.join("b")]
+        );
+    }
+}
@@ -0,0 +1,508 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    path::Path,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    time::Duration,
+};
+
+use anyhow::{bail, Context};
+use image::{
+    codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder},
+    AnimationDecoder, Frame, Frames, ImageFormat,
+};
+
+/// Number of decoded frames buffered ahead of playback when streaming.
+const FRAME_BUFFER: usize = 4;
+
+/// Delays of this or less, often 0 in files meaning "as fast as possible", play at
+/// [`DEFAULT_FRAME_DELAY`] instead. Firefox and Chrome do the same for gifs.
+const MAX_REPLACED_DELAY: Duration = Duration::from_millis(10);
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// Bytes of decoded frames an animation may keep in memory before it is streamed instead,
+/// unless its manifest sets `memory_budget`.
+pub const DEFAULT_MEMORY_BUDGET: u64 = 256 * 1024 * 1024;
+
+/// Bytes of a manifest's `memory_budget` in megabytes, or [`DEFAULT_MEMORY_BUDGET`].
+pub fn memory_budget(megabytes: Option<u32>) -> u64 {
+    megabytes.map_or(DEFAULT_MEMORY_BUDGET, |mb| mb as u64 * 1024 * 1024)
+}
+
+/// Decodes the frames of an animated gif, png or webp lazily, each composited to the full
+/// canvas size. The format is detected from the file's signature, not its extension.
+pub fn decode_frames(path: &Path) -> anyhow::Result<Frames<'static>> {
+    let input = File::open(path).with_context(|| format!("failed to open `{}`", path.display()))?;
+    let mut reader = BufReader::new(input);
+    let header = reader
+        .fill_buf()
+        .with_context(|| format!("failed to read `{}`", path.display()))?;
+
+    let decode_error = || format!("failed to decode `{}`", path.display());
+    let frames = match image::guess_format(header) {
+        Ok(ImageFormat::Gif) => GifDecoder::new(reader)
+            .with_context(decode_error)?
+            .into_frames(),
+        Ok(ImageFormat::Png) => {
+            let decoder = PngDecoder::new(reader).with_context(decode_error)?;
+            if !decoder.is_apng() {
+                bail!("`{}` is a still png, not an animation", path.display());
+            }
+            decoder.apng().into_frames()
+        }
+        Ok(ImageFormat::WebP) => {
+            let decoder = WebPDecoder::new(reader).with_context(decode_error)?;
+            if !decoder.has_animation() {
+                bail!("`{}` is a still webp, not an animation", path.display());
+            }
+            decoder.into_frames()
+        }
+        _ => bail!("`{}` is not a gif, animated png or webp", path.display()),
+    };
+    Ok(frames)
+}
+
+/// Number of frames in an animated gif, png or webp, read from the file's structure rather
+/// than by compositing the frames, so it is cheap even for long animations.
+pub fn frame_count(path: &Path) -> anyhow::Result<usize> {
+    let input = File::open(path).with_context(|| format!("failed to open `{}`", path.display()))?;
+    let mut reader = BufReader::new(input);
+    let header = reader
+        .fill_buf()
+        .with_context(|| format!("failed to read `{}`", path.display()))?;
+
+    let count = match image::guess_format(header) {
+        Ok(ImageFormat::Gif) => gif_frame_count(reader),
+        Ok(ImageFormat::Png) => apng_frame_count(reader),
+        Ok(ImageFormat::WebP) => webp_frame_count(reader),
+        _ => bail!("`{}` is not a gif, animated png or webp", path.display()),
+    };
+    count.with_context(|| format!("failed to count the frames of `{}`", path.display()))
+}
+
+/// Walks the frame descriptors, skipping the image data.
+fn gif_frame_count(reader: impl Read) -> anyhow::Result<usize> {
+    let mut decoder = gif::DecodeOptions::new().read_info(reader)?;
+    let mut count = 0;
+    while decoder.next_frame_info()?.is_some() {
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Reads the frame count from the `acTL` chunk, which comes before the image data.
+fn apng_frame_count(mut reader: impl Read + Seek) -> anyhow::Result<usize> {
+    reader.seek(SeekFrom::Start(8))?;
+    loop {
+        let mut header = [0; 8];
+        reader.read_exact(&mut header)?;
+        let length = u32::from_be_bytes(header[..4].try_into().unwrap());
+        match &header[4..] {
+            b"acTL" => {
+                let mut frames = [0; 4];
+                reader.read_exact(&mut frames)?;
+                return Ok(u32::from_be_bytes(frames) as usize);
+            }
+            b"IDAT" | b"IEND" => bail!("not an animated png"),
+            // Skips the data and the checksum
+            _ => reader.seek(SeekFrom::Current(length as i64 + 4))?,
+        };
+    }
+}
+
+/// Counts the `ANMF` chunks of the RIFF container, one per frame. Bytes past the size
+/// in the RIFF header aren't part of the file.
+fn webp_frame_count(mut reader: impl Read + Seek) -> anyhow::Result<usize> {
+    let mut riff = [0; 12];
+    reader.read_exact(&mut riff)?;
+    let end = 8 + u32::from_le_bytes(riff[4..8].try_into().unwrap()) as u64;
+    let mut position = 12;
+    let mut count = 0;
+    while position + 8 <= end {
+        let mut header = [0; 8];
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        if &header[..4] == b"ANMF" {
+            count += 1;
+        }
+        // Chunks are padded to an even size
+        let length = u32::from_le_bytes(header[4..].try_into().unwrap()) as u64;
+        let padded = length + (length & 1);
+        position += 8 + padded;
+        reader.seek(SeekFrom::Current(padded as i64))?;
+    }
+    Ok(count)
+}
+
+/// Whether `path` is an animated gif, png or webp, judged by its contents.
+pub fn is_animated(path: &Path) -> bool {
+    decode_frames(path).is_ok()
+}
+
+/// How long `frame` stays on screen at normal speed.
+pub fn frame_delay(frame: &Frame) -> Duration {
+    let (num, denum) = frame.delay().numer_denom_ms();
+    let delay = Duration::from_millis((num / denum.max(1)) as u64);
+    if delay <= MAX_REPLACED_DELAY {
+        DEFAULT_FRAME_DELAY
+    } else {
+        delay
+    }
+}
+
+/// Index of the frame on screen `position` into one pass of `frames`, counting from the
+/// last frame when playing in `reverse`.
+pub fn frame_at(frames: &[Frame], mut position: Duration, reverse: bool) -> usize {
+    let order = |i| if reverse { frames.len() - 1 - i } else { i };
+    for i in (0..frames.len()).map(order) {
+        let delay = frame_delay(&frames[i]);
+        if position < delay {
+            return i;
+        }
+        position -= delay;
+    }
+    order(frames.len() - 1)
+}
+
+/// How far an animation has played, derived from the time since it started rather than
+/// counted frames, so it doesn't drift and slow renders skip frames instead of slowing down.
+/// `now` is always a [`Clock`] reading.
+///
+/// [`Clock`]: super::clock::Clock
+#[derive(Debug, Clone, Copy)]
+pub struct Playback {
+    /// Time played up to `resumed`, scaled by speed.
+    played: Duration,
+    /// When playback last (re)started or sought. `None` before the first update and while
+    /// paused.
+    resumed: Option<Duration>,
+    paused: bool,
+    speed: f32,
+}
+
+impl Playback {
+    pub fn new(speed: f32) -> Self {
+        Playback {
+            played: Duration::ZERO,
+            resumed: None,
+            paused: false,
+            speed: speed.max(0.0),
+        }
+    }
+
+    /// Starts the clock unless it is already running or paused. Called on every update, so
+    /// playback begins when the animation is first shown.
+    pub fn start(&mut self, now: Duration) {
+        if !self.paused && self.resumed.is_none() {
+            self.resumed = Some(now);
+        }
+    }
+
+    /// Time played so far, scaled by speed.
+    pub fn played(&self, now: Duration) -> Duration {
+        match self.resumed {
+            Some(resumed) => self.played + now.saturating_sub(resumed).mul_f32(self.speed),
+            None => self.played,
+        }
+    }
+
+    /// Jumps to `played`.
+    pub fn seek(&mut self, now: Duration, played: Duration) {
+        self.played = played;
+        if self.resumed.is_some() {
+            self.resumed = Some(now);
+        }
+    }
+
+    pub fn pause(&mut self, now: Duration) {
+        self.played = self.played(now);
+        self.resumed = None;
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self, now: Duration) {
+        if self.paused {
+            self.paused = false;
+            self.resumed = Some(now);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Changes the rate from the current position on, 1.0 being normal speed.
+    pub fn set_speed(&mut self, now: Duration, speed: f32) {
+        self.seek(now, self.played(now));
+        self.speed = speed.max(0.0);
+    }
+}
+
+/// Frames of an animation decoded on a background thread into a small bounded queue,
+/// starting over after the last one, for animations too large to keep decoded in memory.
+pub struct FrameStream {
+    frames: Receiver<Frame>,
+}
+
+impl FrameStream {
+    /// Starts decoding, leaving out the first `skip` frames the first time through, e.g.
+    /// ones already on screen.
+    pub fn spawn(path: &Path, skip: usize) -> anyhow::Result<Self> {
+        let (sender, frames) = mpsc::sync_channel(FRAME_BUFFER);
+        let thread_path = path.to_path_buf();
+        std::thread::Builder::new()
+            .name("animation decoder".to_string())
+            .spawn(move || {
+                let mut skip = skip;
+                loop {
+                    let Ok(frames) = decode_frames(&thread_path) else {
+                        break;
+                    };
+                    let mut decoded = 0;
+                    for frame in frames.skip(skip) {
+                        // A broken frame ends the stream, like the end of a video would
+                        let Ok(frame) = frame else {
+                            return;
+                        };
+                        // Stops once the stream is dropped
+                        if sender.send(frame).is_err() {
+                            return;
+                        }
+                        decoded += 1;
+                    }
+                    if decoded == 0 && skip == 0 {
+                        break;
+                    }
+                    skip = 0;
+                }
+            })?;
+
+        Ok(FrameStream { frames })
+    }
+
+    /// The next decoded frame, `Ok(None)` if it isn't ready yet and an error once decoding
+    /// has failed. With `wait`, blocks until the frame is decoded instead.
+    pub fn next_frame(&self, wait: bool) -> Result<Option<Frame>, TryRecvError> {
+        if wait {
+            return self
+                .frames
+                .recv()
+                .map(Some)
+                .map_err(|_| TryRecvError::Disconnected);
+        }
+        match self.frames.try_recv() {
+            Ok(frame) => Ok(Some(frame)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::path::PathBuf;
+
+    use image::{codecs::gif::GifEncoder, Delay, Rgba, RgbaImage};
+
+    use super::*;
+
+    /// Writes `bytes` to a file named after the calling test, removed when dropped.
+    pub(crate) struct TempFile(pub PathBuf);
+
+    impl TempFile {
+        pub(crate) fn new(name: &str, bytes: &[u8]) -> Self {
+            let path = std::env::temp_dir().join(format!("hemera-{}-{name}", std::process::id()));
+            std::fs::write(&path, bytes).unwrap();
+            TempFile(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    /// A gif of `count` frames of `size` pixels, each shown for `delay_ms`.
+    pub(crate) fn gif(count: u8, size: u32, delay_ms: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut encoder = GifEncoder::new(&mut bytes);
+        for i in 0..count {
+            let image = RgbaImage::from_pixel(size, size, Rgba([i * 50, 0, 0, 255]));
+            let delay = Delay::from_numer_denom_ms(delay_ms, 1);
+            encoder
+                .encode_frame(Frame::from_parts(image, 0, 0, delay))
+                .unwrap();
+        }
+        drop(encoder);
+        bytes
+    }
+
+    /// A png made of `chunks`, with zeroes for checksums.
+    fn png(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut bytes = b"\x89PNG\r\n\x1a\n".to_vec();
+        for (name, data) in chunks {
+            bytes.extend((data.len() as u32).to_be_bytes());
+            bytes.extend(*name);
+            bytes.extend(*data);
+            bytes.extend([0; 4]);
+        }
+        bytes
+    }
+
+    /// A webp made of `chunks`, padded to even sizes.
+    fn webp(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut body = b"WEBP".to_vec();
+        for (name, data) in chunks {
+            body.extend(*name);
+            body.extend((data.len() as u32).to_le_bytes());
+            body.extend(*data);
+            if data.len() % 2 == 1 {
+                body.push(0);
+            }
+        }
+        let mut bytes = b"RIFF".to_vec();
+        bytes.extend((body.len() as u32).to_le_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    #[test]
+    fn counts_gif_frames() {
+        let file = TempFile::new("count.gif", &gif(4, 8, 100));
+        assert_eq!(frame_count(&file.0).unwrap(), 4);
+    }
+
+    #[test]
+    fn counts_apng_frames_from_the_animation_control() {
+        let header = (b"IHDR", &[0; 13][..]);
+        let data = (b"IDAT", &[][..]);
+        let animated = png(&[header, (b"acTL", &[0, 0, 0, 5, 0, 0, 0, 0]), data]);
+        let file = TempFile::new("count.png", &animated);
+        assert_eq!(frame_count(&file.0).unwrap(), 5);
+
+        let file = TempFile::new("still.png", &png(&[header, data]));
+        assert!(frame_count(&file.0).is_err());
+    }
+
+    #[test]
+    fn counts_webp_frames_across_padding() {
+        let file = TempFile::new(
+            "count.webp",
+            &webp(&[
+                (b"VP8X", &[0x12, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+                (b"ANIM", &[0; 6]),
+                (b"ANMF", &[0; 17]),
+                (b"ANMF", &[0; 20]),
+                (b"ANMF", &[0; 3]),
+            ]),
+        );
+        assert_eq!(frame_count(&file.0).unwrap(), 3);
+    }
+
+    #[test]
+    fn counts_webp_frames_within_the_riff_size() {
+        let mut bytes = webp(&[(b"ANIM", &[0; 6]), (b"ANMF", &[0; 4])]);
+        // Trailing bytes after the RIFF chunk, which look like another frame
+        bytes.extend(b"ANMF\x04\0\0\0\0\0\0\0");
+        let file = TempFile::new("trailing.webp", &bytes);
+        assert_eq!(frame_count(&file.0).unwrap(), 1);
+
+        // A chunk length of u32::MAX, odd, is padded past the u32 range
+        let mut bytes = webp(&[(b"ANMF", &[0; 2])]);
+        bytes.extend(b"ANMF\xff\xff\xff\xff");
+        let riff_size = bytes.len() as u32 - 8;
+        bytes[4..8].copy_from_slice(&riff_size.to_le_bytes());
+        let file = TempFile::new("huge.webp", &bytes);
+        assert_eq!(frame_count(&file.0).unwrap(), 2);
+    }
+
+    /// Blank frames shown for `delays` milliseconds each.
+    fn frames(delays: &[u32]) -> Vec<Frame> {
+        delays
+            .iter()
+            .map(|&delay| {
+                let delay = Delay::from_numer_denom_ms(delay, 1);
+                Frame::from_parts(RgbaImage::new(1, 1), 0, 0, delay)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn frames_follow_their_delays() {
+        let frames = frames(&[100, 200, 300]);
+        let at = |ms, reverse| frame_at(&frames, Duration::from_millis(ms), reverse);
+
+        assert_eq!(
+            [0, 99, 100, 299, 300, 599].map(|ms| at(ms, false)),
+            [0, 0, 1, 1, 2, 2]
+        );
+        // Reversed, the last frame comes first and keeps its own delay
+        assert_eq!(
+            [0, 299, 300, 499, 500, 599].map(|ms| at(ms, true)),
+            [2, 2, 1, 1, 0, 0]
+        );
+        // Past the end of the pass, the final frame stays
+        assert_eq!(at(10_000, false), 2);
+        assert_eq!(at(10_000, true), 0);
+    }
+
+    #[test]
+    fn zero_delays_play_at_the_default_rate() {
+        let frames = frames(&[0, 10, 20]);
+        assert_eq!(frame_delay(&frames[0]), DEFAULT_FRAME_DELAY);
+        assert_eq!(frame_delay(&frames[1]), DEFAULT_FRAME_DELAY);
+        assert_eq!(frame_delay(&frames[2]), Duration::from_millis(20));
+        assert_eq!(frame_at(&frames, Duration::from_millis(150), false), 1);
+    }
+
+    #[test]
+    fn negative_speeds_stop_playback() {
+        let mut playback = Playback::new(-1.0);
+        playback.start(Duration::ZERO);
+        assert_eq!(playback.played(Duration::from_secs(1)), Duration::ZERO);
+    }
+
+    #[test]
+    fn playback_starts_when_first_shown() {
+        let mut playback = Playback::new(2.0);
+        assert_eq!(playback.played(Duration::from_secs(5)), Duration::ZERO);
+
+        playback.start(Duration::from_secs(5));
+        playback.start(Duration::from_secs(6));
+        assert_eq!(
+            playback.played(Duration::from_secs(7)),
+            Duration::from_secs(4)
+        );
+    }
+
+    #[test]
+    fn playback_pauses_seeks_and_changes_speed_in_place() {
+        let secs = Duration::from_secs;
+        let mut playback = Playback::new(1.0);
+        playback.start(secs(0));
+
+        playback.pause(secs(3));
+        // Updates keep calling start, which doesn't resume
+        playback.start(secs(4));
+        assert_eq!(playback.played(secs(10)), secs(3));
+        // Seeking while paused stays paused, and may go past the end of the animation
+        playback.seek(secs(10), secs(100));
+        assert_eq!(playback.played(secs(20)), secs(100));
+
+        playback.resume(secs(20));
+        assert_eq!(playback.played(secs(21)), secs(101));
+        playback.set_speed(secs(21), 0.5);
+        assert_eq!(playback.played(secs(23)), secs(102));
+        // Negative speeds stop instead of playing backwards
+        playback.set_speed(secs(23), -1.0);
+        assert_eq!(playback.played(secs(30)), secs(102));
+    }
+}
@@ -0,0 +1,294 @@
+use std::time::Duration;
+
+use anyhow::bail;
+use serde::Deserialize;
+
+use super::timeline::Easing;
+
+/// Keyframes animating an image, as written in a manifest's `animation` table.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Animation {
+    pub keyframes: Vec<Keyframe>,
+    /// What happens after the last keyframe.
+    #[serde(default)]
+    pub repeat: Repeat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Repeat {
+    /// Hold the last keyframe.
+    #[default]
+    Once,
+    /// Jump back to the first keyframe.
+    Loop,
+    /// Play backwards to the first keyframe, then forwards again.
+    PingPong,
+}
+
+/// Values an image takes at `time`, see `ImageManifest` for their meaning. Properties a
+/// keyframe leaves out are interpolated between the neighbouring keyframes that set them.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Keyframe {
+    /// Seconds since the scene started.
+    pub time: f32,
+    /// Curve of the segment leading to this keyframe.
+    #[serde(default)]
+    pub easing: Easing,
+    #[serde(default)]
+    pub offset: Option<[f32; 2]>,
+    #[serde(
+        default,
+        deserialize_with = "crate::manifest::deserialize_optional_scale"
+    )]
+    pub scale: Option<[f32; 2]>,
+    #[serde(default)]
+    pub rotation: Option<f32>,
+    #[serde(default)]
+    pub opacity: Option<f32>,
+    #[serde(default)]
+    pub tint: Option<[f32; 3]>,
+}
+
+/// The animated properties at one point in time. `None` for properties no keyframe sets.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Properties {
+    pub offset: Option<[f32; 2]>,
+    pub scale: Option<[f32; 2]>,
+    pub rotation: Option<f32>,
+    pub opacity: Option<f32>,
+    pub tint: Option<[f32; 3]>,
+}
+
+impl Animation {
+    /// Time of the last keyframe.
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    /// The animated properties `elapsed` after the start.
+    pub fn sample(&self, elapsed: Duration) -> Properties {
+        let duration = self.duration();
+        let mut time = elapsed.as_secs_f32();
+        if duration > 0.0 {
+            time = match self.repeat {
+                Repeat::Once => time.min(duration),
+                Repeat::Loop => time % duration,
+                Repeat::PingPong => {
+                    let time = time % (2.0 * duration);
+                    duration - (time - duration).abs()
+                }
+            };
+        }
+
+        Properties {
+            offset: self.sample_property(time, |k| k.offset, lerp_array),
+            scale: self.sample_property(time, |k| k.scale, lerp_array),
+            rotation: self.sample_property(time, |k| k.rotation, lerp),
+            opacity: self.sample_property(time, |k| k.opacity, lerp),
+            tint: self.sample_property(time, |k| k.tint, lerp_array),
+        }
+    }
+
+    fn sample_property<T: Copy>(
+        &self,
+        time: f32,
+        get: impl Fn(&Keyframe) -> Option<T>,
+        lerp: impl Fn(T, T, f32) -> T,
+    ) -> Option<T> {
+        let mut keys = self
+            .keyframes
+            .iter()
+            .filter_map(|k| get(k).map(|value| (k, value)));
+
+        let (mut from, mut from_value) = keys.next()?;
+        if time < from.time {
+            return Some(from_value);
+        }
+        for (to, to_value) in keys {
+            if time < to.time {
+                let t = (time - from.time) / (to.time - from.time);
+                return Some(lerp(from_value, to_value, to.easing.apply(t)));
+            }
+            (from, from_value) = (to, to_value);
+        }
+        Some(from_value)
+    }
+
+    /// Checks that there are keyframes, in order, with values in range.
+    pub fn validate(&self, key: &str) -> anyhow::Result<()> {
+        if self.keyframes.is_empty() {
+            bail!("`{key}.keyframes`: at least one keyframe is required");
+        }
+
+        let mut previous = 0.0;
+        for (i, keyframe) in self.keyframes.iter().enumerate() {
+            let key = format!("{key}.keyframes[{i}]");
+            if !keyframe.time.is_finite() || keyframe.time < previous {
+                bail!("`{key}.time`: must not be before the previous keyframe");
+            }
+            previous = keyframe.time;
+
+            if let Err(e) = keyframe.easing.validate() {
+                bail!("`{key}.easing`: {e}");
+            }
+            if keyframe
+                .scale
+                .is_some_and(|scale| scale.iter().any(|s| !s.is_finite() || *s <= 0.0))
+            {
+                bail!("`{key}.scale`: must be greater than 0");
+            }
+            if keyframe
+                .opacity
+                .is_some_and(|opacity| !(0.0..=1.0).contains(&opacity))
+            {
+                bail!("`{key}.opacity`: must be between 0.0 and 1.0");
+            }
+            if keyframe
+                .tint
+                .is_some_and(|tint| tint.iter().any(|c| !(0.0..=1.0).contains(c)))
+            {
+                bail!("`{key}.tint`: components must be between 0.0 and 1.0");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+fn lerp_array<const N: usize>(from: [f32; N], to: [f32; N], t: f32) -> [f32; N] {
+    std::array::from_fn(|i| lerp(from[i], to[i], t))
+}
+
+/// Plays an [`Animation`], starting on the first sample.
+#[derive(Debug, Clone)]
+pub struct Animator {
+    pub animation: Animation,
+    /// Clock reading of the first sample.
+    pub started: Option<Duration>,
+}
+
+impl Animator {
+    pub fn new(animation: Animation) -> Self {
+        Animator {
+            animation,
+            started: None,
+        }
+    }
+
+    /// The animated properties at `now`, a clock reading.
+    pub fn sample(&mut self, now: Duration) -> Properties {
+        let started = *self.started.get_or_insert(now);
+        self.animation.sample(now.saturating_sub(started))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rotation keyframes at `(seconds, degrees)`, eased linearly.
+    fn rotation(repeat: Repeat, keyframes: &[(f32, f32)]) -> Animation {
+        Animation {
+            keyframes: keyframes
+                .iter()
+                .map(|&(time, rotation)| Keyframe {
+                    time,
+                    easing: Easing::Linear,
+                    offset: None,
+                    scale: None,
+                    rotation: Some(rotation),
+                    opacity: None,
+                    tint: None,
+                })
+                .collect(),
+            repeat,
+        }
+    }
+
+    fn rotation_at(animation: &Animation, seconds: f32) -> f32 {
+        animation
+            .sample(Duration::from_secs_f32(seconds))
+            .rotation
+            .unwrap()
+    }
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn once_holds_the_ends() {
+        let animation = rotation(Repeat::Once, &[(1.0, 0.0), (3.0, 100.0)]);
+        assert_near(rotation_at(&animation, 0.0), 0.0);
+        assert_near(rotation_at(&animation, 2.0), 50.0);
+        assert_near(rotation_at(&animation, 3.0), 100.0);
+        assert_near(rotation_at(&animation, 100.0), 100.0);
+    }
+
+    #[test]
+    fn loop_wraps_to_the_first_keyframe() {
+        let animation = rotation(Repeat::Loop, &[(0.0, 0.0), (2.0, 100.0)]);
+        assert_near(rotation_at(&animation, 1.9), 95.0);
+        assert_near(rotation_at(&animation, 2.0), 0.0);
+        assert_near(rotation_at(&animation, 2.5), 25.0);
+        assert_near(rotation_at(&animation, 41.0), 50.0);
+    }
+
+    #[test]
+    fn ping_pong_turns_around_at_both_ends() {
+        let animation = rotation(Repeat::PingPong, &[(0.0, 0.0), (2.0, 100.0)]);
+        assert_near(rotation_at(&animation, 1.5), 75.0);
+        assert_near(rotation_at(&animation, 2.0), 100.0);
+        assert_near(rotation_at(&animation, 2.5), 75.0);
+        assert_near(rotation_at(&animation, 4.0), 0.0);
+        assert_near(rotation_at(&animation, 4.5), 25.0);
+        assert_near(rotation_at(&animation, 22.0), 100.0);
+    }
+
+    #[test]
+    fn zero_length_segments_jump_to_the_later_keyframe() {
+        let animation = rotation(
+            Repeat::Once,
+            &[
+                (0.0, 0.0),
+                (0.0, 10.0),
+                (1.0, 20.0),
+                (1.0, 80.0),
+                (2.0, 100.0),
+            ],
+        );
+        assert_near(rotation_at(&animation, 0.0), 10.0);
+        assert_near(rotation_at(&animation, 0.5), 15.0);
+        assert_near(rotation_at(&animation, 1.0), 80.0);
+        assert_near(rotation_at(&animation, 1.5), 90.0);
+
+        // All at once, repeating or not
+        for repeat in [Repeat::Once, Repeat::Loop, Repeat::PingPong] {
+            let animation = rotation(repeat, &[(0.0, 10.0), (0.0, 20.0)]);
+            assert_near(rotation_at(&animation, 0.0), 20.0);
+            assert_near(rotation_at(&animation, 5.0), 20.0);
+        }
+    }
+
+    #[test]
+    fn properties_interpolate_between_the_keyframes_setting_them() {
+        let mut animation = rotation(Repeat::Once, &[(0.0, 0.0), (1.0, 50.0), (2.0, 100.0)]);
+        animation.keyframes[0].opacity = Some(0.0);
+        animation.keyframes[2].opacity = Some(1.0);
+        animation.keyframes[2].easing = Easing::EaseIn;
+
+        let sample = animation.sample(Duration::from_secs(1));
+        assert_near(sample.opacity.unwrap(), 0.5 * 0.5 * 0.5);
+        assert_eq!(sample.offset, None);
+    }
+}
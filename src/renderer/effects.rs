@@ -0,0 +1,541 @@
+use std::time::Duration;
+
+use anyhow::bail;
+use serde::Deserialize;
+use wgpu::{util::DeviceExt, CommandEncoder, Device, Queue, SurfaceConfiguration, TextureView};
+
+use super::{
+    primitives::{plane::Plane, vertex::Vertex},
+    validation,
+};
+
+/// The built-in post-processing effects, each backed by a shader in `shaders/effects/`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectKind {
+    /// Gaussian blur of `radius` pixels.
+    Blur,
+    /// Darkens the corners by `strength`, from `radius` outwards over `softness`.
+    Vignette,
+    /// Noise of `strength` changing every frame at 24 fps.
+    FilmGrain,
+    /// `brightness`, `contrast`, `saturation` and `gamma`.
+    ColorGrading,
+    /// Unsharp mask of `strength`.
+    Sharpen,
+    /// Red and blue split apart by `offset` pixels at the edges.
+    ChromaticAberration,
+}
+
+impl EffectKind {
+    pub const ALL: [EffectKind; 6] = [
+        EffectKind::Blur,
+        EffectKind::Vignette,
+        EffectKind::FilmGrain,
+        EffectKind::ColorGrading,
+        EffectKind::Sharpen,
+        EffectKind::ChromaticAberration,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EffectKind::Blur => "blur",
+            EffectKind::Vignette => "vignette",
+            EffectKind::FilmGrain => "film_grain",
+            EffectKind::ColorGrading => "color_grading",
+            EffectKind::Sharpen => "sharpen",
+            EffectKind::ChromaticAberration => "chromatic_aberration",
+        }
+    }
+
+    pub fn shader_source(&self) -> &'static str {
+        match self {
+            EffectKind::Blur => include_str!("../renderer/shaders/effects/blur.wgsl"),
+            EffectKind::Vignette => include_str!("../renderer/shaders/effects/vignette.wgsl"),
+            EffectKind::FilmGrain => include_str!("../renderer/shaders/effects/film_grain.wgsl"),
+            EffectKind::ColorGrading => {
+                include_str!("../renderer/shaders/effects/color_grading.wgsl")
+            }
+            EffectKind::Sharpen => include_str!("../renderer/shaders/effects/sharpen.wgsl"),
+            EffectKind::ChromaticAberration => {
+                include_str!("../renderer/shaders/effects/chromatic_aberration.wgsl")
+            }
+        }
+    }
+
+    /// The `[[effects]]` keys this effect reads, besides `name`.
+    pub fn parameters(&self) -> &'static [&'static str] {
+        match self {
+            EffectKind::Blur => &["radius"],
+            EffectKind::Vignette => &["strength", "radius", "softness"],
+            EffectKind::FilmGrain | EffectKind::Sharpen => &["strength"],
+            EffectKind::ColorGrading => &["brightness", "contrast", "saturation", "gamma"],
+            EffectKind::ChromaticAberration => &["offset"],
+        }
+    }
+
+    /// The shader's `params` for each pass the effect takes, filling in defaults for unset
+    /// keys. Blurs take two, horizontal then vertical.
+    pub fn passes(&self, settings: &EffectSettings) -> Vec<[f32; 4]> {
+        match self {
+            EffectKind::Blur => {
+                let radius = settings.radius.unwrap_or(4.0);
+                vec![[radius, 1.0, 0.0, 0.0], [radius, 0.0, 1.0, 0.0]]
+            }
+            EffectKind::Vignette => vec![[
+                settings.strength.unwrap_or(0.5),
+                settings.radius.unwrap_or(0.75),
+                settings.softness.unwrap_or(0.45),
+                0.0,
+            ]],
+            EffectKind::FilmGrain => vec![[settings.strength.unwrap_or(0.08), 0.0, 0.0, 0.0]],
+            EffectKind::ColorGrading => vec![[
+                settings.brightness.unwrap_or(0.0),
+                settings.contrast.unwrap_or(1.0),
+                settings.saturation.unwrap_or(1.0),
+                settings.gamma.unwrap_or(1.0),
+            ]],
+            EffectKind::Sharpen => vec![[settings.strength.unwrap_or(0.5), 0.0, 0.0, 0.0]],
+            EffectKind::ChromaticAberration => {
+                vec![[settings.offset.unwrap_or(2.0), 0.0, 0.0, 0.0]]
+            }
+        }
+    }
+}
+
+/// One post-processing effect, as written in an entry of a manifest's `[[effects]]` array.
+/// Only the parameters the effect reads (see [`EffectKind::parameters`]) may be set.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EffectSettings {
+    pub name: EffectKind,
+    /// Blur radius in pixels, or where a vignette starts, from 0 at the center to 1 at the
+    /// corners.
+    #[serde(default)]
+    pub radius: Option<f32>,
+    #[serde(default)]
+    pub strength: Option<f32>,
+    /// Width of a vignette's falloff, in the units of `radius`.
+    #[serde(default)]
+    pub softness: Option<f32>,
+    /// Color split in pixels.
+    #[serde(default)]
+    pub offset: Option<f32>,
+    /// Added to every channel.
+    #[serde(default)]
+    pub brightness: Option<f32>,
+    /// 1.0 leaves colors unchanged.
+    #[serde(default)]
+    pub contrast: Option<f32>,
+    /// 1.0 leaves colors unchanged, 0.0 is grayscale.
+    #[serde(default)]
+    pub saturation: Option<f32>,
+    /// 1.0 leaves colors unchanged, higher values brighten midtones.
+    #[serde(default)]
+    pub gamma: Option<f32>,
+}
+
+impl EffectSettings {
+    pub fn new(name: EffectKind) -> Self {
+        EffectSettings {
+            name,
+            radius: None,
+            strength: None,
+            softness: None,
+            offset: None,
+            brightness: None,
+            contrast: None,
+            saturation: None,
+            gamma: None,
+        }
+    }
+
+    /// Rejects parameters the effect doesn't read and values outside their range.
+    pub fn validate(&self, key: &str) -> anyhow::Result<()> {
+        let value = |value: Option<f32>| value.map(|value| vec![value]);
+        validation::validate_parameters(
+            key,
+            &format!("`{}`", self.name.name()),
+            self.name.parameters(),
+            vec![
+                ("radius", value(self.radius)),
+                ("strength", value(self.strength)),
+                ("softness", value(self.softness)),
+                ("offset", value(self.offset)),
+                ("brightness", value(self.brightness)),
+                ("contrast", value(self.contrast)),
+                ("saturation", value(self.saturation)),
+                ("gamma", value(self.gamma)),
+            ],
+        )?;
+
+        let non_negative = [
+            ("radius", self.radius),
+            ("strength", self.strength),
+            ("softness", self.softness),
+            ("offset", self.offset),
+            ("contrast", self.contrast),
+            ("saturation", self.saturation),
+        ];
+        for (parameter, value) in non_negative {
+            if value.is_some_and(|value| value < 0.0) {
+                bail!("`{key}.{parameter}`: must not be negative");
+            }
+        }
+        if self.name == EffectKind::Blur && self.radius.is_some_and(|radius| radius > 64.0) {
+            bail!("`{key}.radius`: blurs are limited to 64 pixels");
+        }
+        if matches!(self.name, EffectKind::Vignette | EffectKind::FilmGrain)
+            && self.strength.is_some_and(|strength| strength > 1.0)
+        {
+            bail!("`{key}.strength`: must be between 0.0 and 1.0");
+        }
+        if self.gamma.is_some_and(|gamma| gamma <= 0.0) {
+            bail!("`{key}.gamma`: must be greater than 0");
+        }
+        Ok(())
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct EffectUniform {
+    /// Size of the output in pixels.
+    pub resolution: [f32; 2],
+    /// Clock time in seconds.
+    pub time: f32,
+    pub _padding: f32,
+    /// See [`EffectKind::passes`].
+    pub params: [f32; 4],
+}
+
+/// A single shader pass of an effect, with its own uniforms.
+pub struct EffectPass {
+    pub uniform: EffectUniform,
+    pub buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+    /// Index into the chain's pipelines, shared by passes of the same kind.
+    pipeline: usize,
+}
+
+/// Post-processing applied to the whole frame after the scene is drawn. The scene renders
+/// into the first of two offscreen targets, each pass then reads one and writes the other,
+/// and the last pass writes to the output.
+pub struct EffectChain {
+    pub passes: Vec<EffectPass>,
+    pipelines: Vec<(EffectKind, wgpu::RenderPipeline)>,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    plane: Plane,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    targets: [wgpu::Texture; 2],
+    /// Bind groups reading each of `targets`.
+    bind_groups: [wgpu::BindGroup; 2],
+}
+
+impl EffectChain {
+    /// Builds the passes of `effects`, in order. `effects` must not be empty.
+    pub fn new(device: &Device, config: &SurfaceConfiguration, effects: &[EffectSettings]) -> Self {
+        let texture_bind_group_layout = Self::texture_bind_group_layout(device);
+        let uniform_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("effect uniform bind group layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let mut pipelines: Vec<(EffectKind, wgpu::RenderPipeline)> = Vec::new();
+        let mut passes = Vec::new();
+        for settings in effects {
+            let kind = settings.name;
+            let pipeline = match pipelines.iter().position(|(k, _)| *k == kind) {
+                Some(index) => index,
+                None => {
+                    pipelines.push((
+                        kind,
+                        Self::create_pipeline(
+                            device,
+                            config.format,
+                            kind,
+                            &texture_bind_group_layout,
+                            &uniform_bind_group_layout,
+                        ),
+                    ));
+                    pipelines.len() - 1
+                }
+            };
+
+            for params in kind.passes(settings) {
+                let uniform = EffectUniform {
+                    resolution: [config.width as f32, config.height as f32],
+                    time: 0.0,
+                    _padding: 0.0,
+                    params,
+                };
+                let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Effect buffer"),
+                    contents: bytemuck::cast_slice(&[uniform]),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                });
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("effect uniform bind group"),
+                    layout: &uniform_bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                });
+                passes.push(EffectPass {
+                    uniform,
+                    buffer,
+                    bind_group,
+                    pipeline,
+                });
+            }
+        }
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let plane = Plane::new(1.0);
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Effect vertex buffer"),
+            contents: bytemuck::cast_slice(&plane.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Effect index buffer"),
+            contents: bytemuck::cast_slice(&plane.get_indices()),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let targets = Self::create_targets(device, config);
+        let bind_groups =
+            Self::create_bind_groups(device, &texture_bind_group_layout, &sampler, &targets);
+
+        EffectChain {
+            passes,
+            pipelines,
+            texture_bind_group_layout,
+            sampler,
+            plane,
+            vertex_buffer,
+            index_buffer,
+            targets,
+            bind_groups,
+        }
+    }
+
+    /// Where the scene is drawn before the effects run.
+    pub fn input_view(&self) -> TextureView {
+        self.targets[0].create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    /// Uploads the time at `now`, a clock reading.
+    pub fn update(&mut self, queue: &Queue, now: Duration) {
+        for pass in &mut self.passes {
+            pass.uniform.time = now.as_secs_f32();
+            queue.write_buffer(&pass.buffer, 0, bytemuck::cast_slice(&[pass.uniform]));
+        }
+    }
+
+    /// Follows a resized output: updates `resolution` and recreates both targets.
+    pub fn resize(&mut self, device: &Device, queue: &Queue, config: &SurfaceConfiguration) {
+        for pass in &mut self.passes {
+            pass.uniform.resolution = [config.width as f32, config.height as f32];
+            queue.write_buffer(&pass.buffer, 0, bytemuck::cast_slice(&[pass.uniform]));
+        }
+        self.targets = Self::create_targets(device, config);
+        self.bind_groups = Self::create_bind_groups(
+            device,
+            &self.texture_bind_group_layout,
+            &self.sampler,
+            &self.targets,
+        );
+    }
+
+    /// Runs every pass over the frame in [`input_view`](Self::input_view), writing the
+    /// result to `view`.
+    pub fn apply(&self, encoder: &mut CommandEncoder, view: &TextureView) {
+        for (i, pass) in self.passes.iter().enumerate() {
+            let target_view;
+            let output = if i + 1 == self.passes.len() {
+                view
+            } else {
+                target_view =
+                    self.targets[(i + 1) % 2].create_view(&wgpu::TextureViewDescriptor::default());
+                &target_view
+            };
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Effect pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: output,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            render_pass.set_pipeline(&self.pipelines[pass.pipeline].1);
+            render_pass.set_bind_group(0, &self.bind_groups[i % 2], &[]);
+            render_pass.set_bind_group(1, &pass.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass.draw_indexed(0..self.plane.get_indices().len() as u32, 0, 0..1);
+        }
+    }
+
+    fn create_pipeline(
+        device: &Device,
+        format: wgpu::TextureFormat,
+        kind: EffectKind,
+        texture_bind_group_layout: &wgpu::BindGroupLayout,
+        uniform_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::RenderPipeline {
+        let vertex_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Effect vertex shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                include_str!("../renderer/shaders/transition_vertex.wgsl").into(),
+            ),
+        });
+        let fragment_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(kind.name()),
+            source: wgpu::ShaderSource::Wgsl(kind.shader_source().into()),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Effect render layout"),
+                bind_group_layouts: &[texture_bind_group_layout, uniform_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Effect pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &vertex_shader,
+                entry_point: "main_vertex",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &fragment_shader,
+                entry_point: "main_fragment",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    }
+
+    fn create_targets(device: &Device, config: &SurfaceConfiguration) -> [wgpu::Texture; 2] {
+        let create = |label| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                size: wgpu::Extent3d {
+                    width: config.width,
+                    height: config.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: config.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                label: Some(label),
+                view_formats: &[],
+            })
+        };
+        [create("Effect target 0"), create("Effect target 1")]
+    }
+
+    /// Layout of group 0 in effect shaders: the input at binding 0 and the sampler at
+    /// binding 1.
+    fn texture_bind_group_layout(device: &Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("effect_texture_bind_group_layout"),
+        })
+    }
+
+    fn create_bind_groups(
+        device: &Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        targets: &[wgpu::Texture; 2],
+    ) -> [wgpu::BindGroup; 2] {
+        let create = |texture: &wgpu::Texture| {
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                ],
+                label: Some("effect bind group"),
+            })
+        };
+        [create(&targets[0]), create(&targets[1])]
+    }
+}
@@ -1,43 +1,76 @@
-use image::{gif::GifDecoder, AnimationDecoder};
-use std::time;
+use std::{collections::VecDeque, path::PathBuf, sync::Arc};
+
+use anyhow::Context;
 use winit::window::Window;
 
-use crate::renderer::primitives::{image::Image, vertex::Vertex};
+use crate::{manifest::Manifest, renderer::primitives::image::BlendMode};
 
 use super::{
-    scenes::{
-        gif_scene::GifScene,
-        scene::{Scene, SceneType},
-        test_image_scene::TestImageScene,
-    },
-    transitions::Transition,
+    clock::{Clock, RealTimeClock},
+    effects::EffectChain,
+    image_effects::ImagePipelines,
+    loader,
+    scenes::scene::{Scene, SceneType},
+    transitions::{InterruptPolicy, Transition, TransitionPipelines, TransitionSpec},
 };
 
-pub struct Engine {
-    pub surface: wgpu::Surface,
-    pub device: wgpu::Device,
-    pub queue: wgpu::Queue,
-    pub config: wgpu::SurfaceConfiguration,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineEvent {
+    /// A transition reached its end, or was snapped to it by a newer one, and has been
+    /// dropped together with its outgoing scene.
+    TransitionFinished,
+    /// A slideshow slide failed to load and was passed over.
+    SlideSkipped { path: PathBuf, error: String },
+}
 
-    pub render_pipeline: wgpu::RenderPipeline,
-    pub scene: Option<SceneType>,
-    pub transition: Option<Transition>,
+pub struct Engine {
+    surface: Option<wgpu::Surface>,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+
+    /// Pipelines of the images drawn with an effect, shared by every scene.
+    image_pipelines: Arc<ImagePipelines>,
+    /// Pipelines of the transition presets, shared by every transition.
+    transition_pipelines: Arc<TransitionPipelines>,
+    scene: Option<SceneType>,
+    transition: Option<Transition>,
+    interrupt_policy: InterruptPolicy,
+    /// Resolution of the transition render targets relative to the output, 1.0 for
+    /// pixel-exact transitions. Lower values trade sharpness for speed on large outputs.
+    transition_quality: f32,
+    /// Time source of every animation. A [`RealTimeClock`] unless replaced, e.g. by a
+    /// [`ManualClock`](super::clock::ManualClock) for deterministic renders.
+    clock: Box<dyn Clock>,
+    /// Post-processing of the loaded manifest, `None` without effects.
+    effects: Option<EffectChain>,
+    /// Cursor position passed to the scenes, see [`Scene::set_cursor`].
+    cursor: Option<[f32; 2]>,
+    queued_transitions: VecDeque<(SceneType, TransitionSpec)>,
+    events: Vec<EngineEvent>,
+
+    // Headless
+    offscreen_texture: Option<wgpu::Texture>,
 
     //Winit
-    pub window: Window,
-    pub size: winit::dpi::PhysicalSize<u32>,
+    window: Option<Window>,
+    size: winit::dpi::PhysicalSize<u32>,
+    /// Physical pixels per logical pixel of the window's monitor, 1.0 when headless.
+    scale_factor: f64,
 }
 
 impl Engine {
-    pub async fn new(window: Window) -> Engine {
+    pub async fn new(window: Window) -> anyhow::Result<Engine> {
         let size = window.inner_size();
+        let scale_factor = window.scale_factor();
 
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
             backends: wgpu::Backends::all(),
             dx12_shader_compiler: Default::default(),
         });
 
-        let surface = unsafe { instance.create_surface(&window) }.unwrap();
+        let surface = unsafe { instance.create_surface(&window) }
+            .context("failed to create a surface for the window")?;
 
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
@@ -46,19 +79,9 @@ impl Engine {
                 force_fallback_adapter: false,
             })
             .await
-            .unwrap();
+            .ok_or_else(|| anyhow::anyhow!("no graphics adapter can draw to the window"))?;
 
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::empty(),
-                    limits: wgpu::Limits::downlevel_defaults(),
-                    label: None,
-                },
-                None,
-            )
-            .await
-            .unwrap();
+        let (device, queue) = Self::request_device(&adapter).await?;
 
         let surface_caps = surface.get_capabilities(&adapter);
         let surface_format = surface_caps
@@ -79,238 +102,382 @@ impl Engine {
         };
         surface.configure(&device, &config);
 
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(
-                include_str!("../renderer/shaders/shader.wgsl").into(),
-            ),
+        let image_pipelines = Arc::new(ImagePipelines::new(&device, config.format));
+        let transition_pipelines = Arc::new(TransitionPipelines::new(&device, config.format));
+
+        Ok(Self {
+            config,
+            device,
+            queue,
+            image_pipelines,
+            transition_pipelines,
+            scene: None,
+            transition: None,
+            interrupt_policy: InterruptPolicy::default(),
+            transition_quality: 1.0,
+            clock: Box::new(RealTimeClock::new()),
+            effects: None,
+            cursor: None,
+            queued_transitions: VecDeque::new(),
+            events: Vec::new(),
+            surface: Some(surface),
+            offscreen_texture: None,
+            size,
+            scale_factor,
+            window: Some(window),
+        })
+    }
+
+    /// Creates an engine without a window, rendering into an offscreen texture of the given size.
+    /// Falls back to a software adapter when no GPU is available.
+    pub async fn new_headless(width: u32, height: u32) -> anyhow::Result<Engine> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            dx12_shader_compiler: Default::default(),
         });
 
-        let texture_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            multisampled: false,
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                ],
-                label: Some("texture_bind_group_layout"),
-            });
+        let mut adapter = None;
+        for force_fallback_adapter in [false, true] {
+            adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    compatible_surface: None,
+                    force_fallback_adapter,
+                })
+                .await;
+            if adapter.is_some() {
+                break;
+            }
+        }
+        let adapter =
+            adapter.ok_or_else(|| anyhow::anyhow!("no suitable graphics adapter found"))?;
 
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[&texture_bind_group_layout],
-                push_constant_ranges: &[],
-            });
+        let (device, queue) = Self::request_device(&adapter).await?;
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[Vertex::desc()],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                unclipped_depth: false,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-        });
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+        };
+
+        let offscreen_texture = Self::create_offscreen_texture(&device, &config);
 
-        // println!("Created");
-        Self {
+        let image_pipelines = Arc::new(ImagePipelines::new(&device, config.format));
+        let transition_pipelines = Arc::new(TransitionPipelines::new(&device, config.format));
+
+        Ok(Self {
             config,
             device,
             queue,
-            render_pipeline,
+            image_pipelines,
+            transition_pipelines,
             scene: None,
             transition: None,
-            surface,
-            size,
-            window,
-        }
+            interrupt_policy: InterruptPolicy::default(),
+            transition_quality: 1.0,
+            clock: Box::new(RealTimeClock::new()),
+            effects: None,
+            cursor: None,
+            queued_transitions: VecDeque::new(),
+            events: Vec::new(),
+            surface: None,
+            offscreen_texture: Some(offscreen_texture),
+            size: winit::dpi::PhysicalSize::new(width, height),
+            scale_factor: 1.0,
+            window: None,
+        })
     }
 
-    pub fn load_scene(&mut self, a: bool) {
-        if a {
-            // self.load_images();
-            self.load_gif2();
-        } else {
-            self.load_gif();
-        }
+    fn create_offscreen_texture(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen texture"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: config.usage,
+            view_formats: &[],
+        })
     }
 
-    pub fn load_transition(&mut self) {
-        let scene = self.scene.take().unwrap();
+    /// Resizes the output: reconfigures the surface, or recreates the offscreen texture when
+    /// headless, refits every scene and recreates the running transition's render targets. Also recovers a
+    /// `Lost` or `Outdated` surface when called with the current size. Zero sizes, as
+    /// reported for minimized windows, are ignored.
+    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        if new_size.width == 0 || new_size.height == 0 {
+            return;
+        }
 
-        let mut transition =
-            Transition::test(&self.device, &self.config, scene, self.config.format);
-        transition.create_index_buffer(&self.device);
-        transition.create_vertex_buffer(&self.device);
+        self.size = new_size;
+        self.config.width = new_size.width;
+        self.config.height = new_size.height;
 
-        self.transition = Some(transition);
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.config);
+        }
+        if self.offscreen_texture.is_some() {
+            self.offscreen_texture =
+                Some(Self::create_offscreen_texture(&self.device, &self.config));
+        }
+        if let Some(scene) = &mut self.scene {
+            scene.resize(&self.device, &self.config);
+        }
+        if let Some(transition) = &mut self.transition {
+            transition.resize(&self.device, &self.config);
+            transition.scene_mut().resize(&self.device, &self.config);
+        }
+        for (scene, _) in &mut self.queued_transitions {
+            scene.resize(&self.device, &self.config);
+        }
+        if let Some(effects) = &mut self.effects {
+            effects.resize(&self.device, &self.queue, &self.config);
+        }
     }
 
-    #[allow(dead_code)]
-    fn load_gif(&mut self) {
-        let input =
-            std::fs::File::open("C:/Users/ernes/Desktop/---/Programming/Rust/Hemera/images/2.gif")
-                .unwrap();
+    /// Handles a cursor moved to `position` in physical pixels from the output's top left,
+    /// or `None` when it left the output.
+    pub fn set_cursor_position(&mut self, position: Option<winit::dpi::PhysicalPosition<f64>>) {
+        let (width, height) = (
+            self.size.width.max(1) as f64,
+            self.size.height.max(1) as f64,
+        );
+        self.cursor = position.map(|position| {
+            [
+                (position.x / width * 2.0 - 1.0).clamp(-1.0, 1.0) as f32,
+                (1.0 - position.y / height * 2.0).clamp(-1.0, 1.0) as f32,
+            ]
+        });
+    }
 
-        let device = &self.device;
-        let decoder = GifDecoder::new(input).unwrap();
-        let frames = decoder.into_frames();
-        let frames = frames.collect_frames().expect("error decoding frames");
+    /// Handles a moved window or a changed monitor DPI, with the new size winit reports.
+    pub fn set_scale_factor(&mut self, scale_factor: f64, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.scale_factor = scale_factor;
+        self.resize(new_size);
+    }
 
-        let a = frames[0].clone();
-        let _b = a.buffer();
+    async fn request_device(
+        adapter: &wgpu::Adapter,
+    ) -> anyhow::Result<(wgpu::Device, wgpu::Queue)> {
+        let info = adapter.get_info();
+        adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    features: wgpu::Features::empty(),
+                    // Textures as large as the adapter allows, for render targets matching
+                    // outputs above 2048 pixels
+                    limits: wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits()),
+                    label: None,
+                },
+                None,
+            )
+            .await
+            .with_context(|| format!("failed to create a device on `{}`", info.name))
+    }
 
-        let gif_frames = frames
-            .iter()
-            .map(|f| {
-                let mut image = Image::test_gif(&self.device, &self.queue, 1.0, f);
+    /// Replaces the time source of every animation, e.g. with a [`ManualClock`] for
+    /// deterministic renders.
+    ///
+    /// [`ManualClock`]: super::clock::ManualClock
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
+    }
 
-                image.create_bind_group(device);
-                image.create_index_buffer(device);
-                image.create_vertex_buffer(device);
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
 
-                let (num, denum) = f.delay().numer_denom_ms();
-                let delay = time::Duration::from_millis((num / denum) as u64);
-                (image, delay)
-            })
-            .collect::<Vec<(Image, time::Duration)>>();
-
-        let gif_scene = GifScene {
-            first_load: true,
-            time_loaded: None,
-            time_till_next_frame: 0,
-            current_frame: 0,
-            frames: gif_frames,
-        };
+    pub fn transition_quality(&self) -> f32 {
+        self.transition_quality
+    }
 
-        let scene = Some(SceneType::Gif(gif_scene));
-        self.scene = scene;
+    /// Sets the resolution of the transition render targets relative to the output, clamped
+    /// to 0.1..=1.0. Applies to transitions starting afterwards, and to scenes switching
+    /// between scenes of their own once they are loaded again.
+    pub fn set_transition_quality(&mut self, quality: f32) {
+        self.transition_quality = quality.clamp(0.1, 1.0);
     }
 
-    fn load_gif2(&mut self) {
-        let input =
-            std::fs::File::open("C:/Users/ernes/Desktop/---/Programming/Rust/Hemera/images/3.gif")
-                .unwrap();
+    pub fn interrupt_policy(&self) -> InterruptPolicy {
+        self.interrupt_policy
+    }
 
-        let device = &self.device;
-        let decoder = GifDecoder::new(input).unwrap();
-        let frames = decoder.into_frames();
-        let frames = frames.collect_frames().expect("error decoding frames");
+    /// Sets what happens to a running transition when another one starts.
+    pub fn set_interrupt_policy(&mut self, policy: InterruptPolicy) {
+        self.interrupt_policy = policy;
+    }
 
-        let a = frames[0].clone();
-        let _b = a.buffer();
+    /// Whether a transition between scenes is running.
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
 
-        let gif_frames = frames
-            .iter()
-            .map(|f| {
-                let mut image = Image::test_gif(&self.device, &self.queue, 1.0, f);
+    pub fn window(&self) -> Option<&Window> {
+        self.window.as_ref()
+    }
 
-                image.create_bind_group(device);
-                image.create_index_buffer(device);
-                image.create_vertex_buffer(device);
+    pub fn size(&self) -> winit::dpi::PhysicalSize<u32> {
+        self.size
+    }
 
-                let (num, denum) = f.delay().numer_denom_ms();
-                let delay = time::Duration::from_millis((num / denum) as u64);
-                (image, delay)
-            })
-            .collect::<Vec<(Image, time::Duration)>>();
-
-        let gif_scene = GifScene {
-            first_load: true,
-            time_loaded: None,
-            time_till_next_frame: 0,
-            current_frame: 0,
-            frames: gif_frames,
-        };
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
 
-        let scene = Some(SceneType::Gif(gif_scene));
-        self.scene = scene;
+    /// The scene on screen, or coming in when a transition is running. `None` until one is
+    /// loaded.
+    pub fn scene_mut(&mut self) -> Option<&mut SceneType> {
+        self.scene.as_mut()
     }
 
-    #[allow(dead_code)] // testing purposes
-    fn load_images(&mut self) {
-        let device = &self.device;
-        let queue = &self.queue;
+    /// The running transition, which holds the outgoing scene.
+    pub fn transition(&self) -> Option<&Transition> {
+        self.transition.as_ref()
+    }
+
+    /// Builds the manifest's scene without showing it, e.g. to pass to
+    /// [`transition_to`](Self::transition_to) later. Effects of the manifest are left out.
+    pub fn build_scene(&self, manifest: &Manifest) -> anyhow::Result<SceneType> {
+        loader::load_scene(
+            &self.device,
+            &self.queue,
+            &self.config,
+            &self.image_pipelines,
+            &self.transition_pipelines,
+            manifest,
+            self.transition_quality,
+        )
+    }
 
-        let image1 = Image::test(device, queue, 0.1, 0.7, 0.7);
-        let image2 = Image::test(device, queue, 0.2, 0.0, 0.0);
-        let image3 = Image::test(device, queue, 0.3, -0.7, 0.2);
+    /// Loads the manifest's scene and switches to it, through the manifest's transition when
+    /// it has one and a scene is already showing.
+    pub fn load_scene(&mut self, manifest: &Manifest) -> anyhow::Result<()> {
+        let transition = match &manifest.transition {
+            Some(settings) => Some(settings.load().context("`transition`")?),
+            None => None,
+        };
+        let scene = self.build_scene(manifest)?;
+        // Effects switch right away, even when the scene transitions
+        self.effects = (!manifest.effects.is_empty())
+            .then(|| EffectChain::new(&self.device, &self.config, &manifest.effects));
+
+        match transition {
+            Some(spec) => self.transition_to(scene, spec),
+            None => {
+                if self.transition.take().is_some() {
+                    self.events.push(EngineEvent::TransitionFinished);
+                }
+                self.queued_transitions.clear();
+                self.scene = Some(scene);
+            }
+        }
 
-        let mut images = vec![image1, image2, image3];
+        Ok(())
+    }
 
-        for image in images.iter_mut() {
-            image.create_bind_group(device);
-            image.create_index_buffer(device);
-            image.create_vertex_buffer(device);
+    /// Switches to `scene` through a transition, following `interrupt_policy` when a
+    /// transition is already running.
+    pub fn transition_to(&mut self, scene: SceneType, spec: TransitionSpec) {
+        if self.transition.is_some() {
+            match self.interrupt_policy {
+                InterruptPolicy::Snap => {
+                    self.transition = None;
+                    self.events.push(EngineEvent::TransitionFinished);
+                }
+                InterruptPolicy::Chain => {
+                    self.queued_transitions.push_back((scene, spec));
+                    return;
+                }
+            }
         }
 
-        let image_scene = TestImageScene { images };
+        self.start_transition(scene, spec);
+    }
 
-        let scene = Some(SceneType::TestImages(image_scene));
-        self.scene = scene;
+    fn start_transition(&mut self, scene: SceneType, spec: TransitionSpec) {
+        let Some(old_scene) = self.scene.replace(scene) else {
+            return;
+        };
+
+        self.transition = Some(Transition::start(
+            &self.device,
+            &self.config,
+            &self.transition_pipelines,
+            old_scene,
+            &spec,
+            self.transition_quality,
+            self.clock.now(),
+        ));
     }
 
-    pub fn update(&mut self) {
+    /// Advances the scenes and the running transition and returns what happened since the
+    /// last update.
+    /// A finished transition is dropped together with the outgoing scene.
+    pub fn update(&mut self) -> Vec<EngineEvent> {
+        let now = self.clock.now();
+
+        let clock = self.clock.as_ref();
+        if let Some(scene) = &mut self.scene {
+            scene.set_cursor(self.cursor);
+            scene.update(&self.device, &self.queue, clock);
+        }
         if let Some(transition) = &mut self.transition {
-            let now = std::time::Instant::now();
-            let diff = now.duration_since(transition.time_started);
+            transition.scene_mut().set_cursor(self.cursor);
             transition
-                .transition_uniform
-                .update_time_offset(diff.as_secs_f32());
-
-            self.queue.write_buffer(
-                &transition.transition_buffer,
-                0,
-                bytemuck::cast_slice(&[transition.transition_uniform]),
-            );
+                .scene_mut()
+                .update(&self.device, &self.queue, clock);
+            transition.update(&self.queue, now);
+        }
+        for scene in self
+            .scene
+            .iter_mut()
+            .chain(self.transition.as_mut().map(|t| t.scene_mut()))
+        {
+            self.events.append(&mut scene.take_events());
+        }
+        if let Some(effects) = &mut self.effects {
+            effects.update(&self.queue, now);
         }
+
+        if self.transition.as_ref().is_some_and(|t| t.is_finished(now)) {
+            self.transition = None;
+            self.events.push(EngineEvent::TransitionFinished);
+
+            if let Some((scene, spec)) = self.queued_transitions.pop_front() {
+                self.start_transition(scene, spec);
+            }
+        }
+
+        std::mem::take(&mut self.events)
     }
 
+    /// Draws a frame. On `SurfaceError::Lost` or `Outdated` the caller should
+    /// [`resize`](Self::resize) to the current size and try again on the next frame.
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
+        let output = match self.surface.as_ref() {
+            Some(surface) => Some(surface.get_current_texture()?),
+            None => None,
+        };
 
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let view = match (output.as_ref(), self.offscreen_texture.as_ref()) {
+            (Some(output), _) => output
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default()),
+            (None, Some(texture)) => texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            (None, None) => return Err(wgpu::SurfaceError::Lost),
+        };
 
         let mut encoder = self
             .device
@@ -318,26 +485,153 @@ impl Engine {
                 label: Some("Render Encoder"),
             });
 
+        // With effects, the scene is drawn offscreen for them to read
+        let effects_input = self.effects.as_ref().map(|effects| effects.input_view());
+        let scene_view = effects_input.as_ref().unwrap_or(&view);
+
+        let pipeline = self
+            .image_pipelines
+            .get(&self.device, None, BlendMode::Alpha);
+        if let Some(transition) = self.transition.as_mut() {
+            transition.transition(&mut encoder, scene_view, &pipeline, self.scene.as_mut());
+        } else if let Some(scene) = self.scene.as_mut() {
+            scene.render_scene(&mut encoder, scene_view, &pipeline);
+        }
+        if let Some(effects) = &self.effects {
+            effects.apply(&mut encoder, &view);
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        if let Some(output) = output {
+            output.present();
+        }
+
+        Ok(())
+    }
+
+    /// Copies the last rendered frame of a headless engine back to the CPU.
+    pub fn read_frame(&self) -> anyhow::Result<image::RgbaImage> {
+        let texture = self
+            .offscreen_texture
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("frames can only be read from a headless engine"))?;
+
+        let width = self.size.width;
+        let height = self.size.height;
+        let unpadded_bytes_per_row = 4 * width;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Frame readback buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Readback Encoder"),
+            });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                aspect: wgpu::TextureAspect::All,
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv()??;
+
+        let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
         {
-            if let Some(scene) = self.scene.as_mut() {
-                scene.render_scene(
-                    &mut encoder,
-                    &view,
-                    // &render_texture.create_view(&wgpu::TextureViewDescriptor::default()),
-                    &self.render_pipeline,
-                )
+            let data = slice.get_mapped_range();
+            for row in data.chunks(padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
             }
         }
+        buffer.unmap();
 
-        {
-            if let Some(transition) = self.transition.as_mut() {
-                transition.transition(&mut encoder, &view, &self.device, &self.render_pipeline);
+        image::RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| anyhow::anyhow!("frame buffer has an unexpected size"))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::renderer::clock::ManualClock;
+
+    /// A headless engine on a manual clock. Without a graphics adapter the test fails, unless
+    /// `HEMERA_SKIP_GPU_TESTS` is set and this returns `None` to skip it.
+    pub(crate) fn headless(width: u32, height: u32) -> Option<(Engine, ManualClock)> {
+        match pollster::block_on(Engine::new_headless(width, height)) {
+            Ok(mut engine) => {
+                let clock = ManualClock::new();
+                engine.set_clock(clock.clone());
+                Some((engine, clock))
             }
+            Err(_) if std::env::var_os("HEMERA_SKIP_GPU_TESTS").is_some() => {
+                eprintln!("skipped: no graphics adapter");
+                None
+            }
+            Err(e) => panic!("{e:#}, set HEMERA_SKIP_GPU_TESTS to skip tests needing one"),
         }
+    }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+    #[test]
+    fn renders_above_2048_pixels() {
+        let Some((mut engine, clock)) = headless(64, 64) else {
+            return;
+        };
+        let (width, height) = (3840, 2160);
+        if engine.device.limits().max_texture_dimension_2d < width {
+            eprintln!("skipped: the adapter's textures are smaller than 4K");
+            return;
+        }
+        engine.resize(winit::dpi::PhysicalSize::new(width, height));
+
+        // Offscreen output, effect targets and transition targets all at the full size
+        let image = concat!(env!("CARGO_MANIFEST_DIR"), "/images/1.png");
+        let manifest = |transition: &str| {
+            Manifest::from_toml(&format!(
+                "effects = [{{ name = \"vignette\" }}]\n{transition}\n[scene.image]\npath = {image:?}"
+            ))
+            .unwrap()
+        };
+        engine.load_scene(&manifest("")).unwrap();
+        engine
+            .load_scene(&manifest("[transition]\nname = \"fade\""))
+            .unwrap();
+        clock.set(Duration::from_millis(500));
+        engine.update();
+        assert!(engine.is_transitioning());
+        engine.render().unwrap();
 
-        Ok(())
+        assert_eq!(engine.read_frame().unwrap().dimensions(), (width, height));
     }
 }
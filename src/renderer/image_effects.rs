@@ -0,0 +1,336 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::bail;
+use serde::Deserialize;
+use wgpu::{Device, RenderPipeline, TextureFormat};
+
+use super::{
+    primitives::{
+        image::{BlendMode, Image},
+        vertex::Vertex,
+    },
+    validation,
+};
+
+/// The built-in per-image effects, each replacing the fragment stage of `shader.wgsl` with
+/// one from `shaders/image_effects/`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageEffectKind {
+    /// Recolors the image with `color`, keeping its brightness, by `amount`.
+    Tint,
+    /// Fades colors to gray by `amount`.
+    Desaturate,
+    /// Sways the image sideways in horizontal bands.
+    Waves,
+    /// Rings spreading from the image's center.
+    Ripple,
+    /// Scrolls the image within its bounds at `velocity`, wrapping around.
+    Scroll,
+    /// Rotates hues by `degrees`, plus `speed` degrees per second.
+    HueShift,
+    /// Brightness swelling and fading by `amount`, `speed` times per second.
+    Pulse,
+}
+
+impl ImageEffectKind {
+    pub const ALL: [ImageEffectKind; 7] = [
+        ImageEffectKind::Tint,
+        ImageEffectKind::Desaturate,
+        ImageEffectKind::Waves,
+        ImageEffectKind::Ripple,
+        ImageEffectKind::Scroll,
+        ImageEffectKind::HueShift,
+        ImageEffectKind::Pulse,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImageEffectKind::Tint => "tint",
+            ImageEffectKind::Desaturate => "desaturate",
+            ImageEffectKind::Waves => "waves",
+            ImageEffectKind::Ripple => "ripple",
+            ImageEffectKind::Scroll => "scroll",
+            ImageEffectKind::HueShift => "hue_shift",
+            ImageEffectKind::Pulse => "pulse",
+        }
+    }
+
+    /// The effect's fragment stage, `fs_effect`, written against the declarations of
+    /// `shader.wgsl`.
+    pub fn fragment_source(&self) -> &'static str {
+        match self {
+            ImageEffectKind::Tint => include_str!("../renderer/shaders/image_effects/tint.wgsl"),
+            ImageEffectKind::Desaturate => {
+                include_str!("../renderer/shaders/image_effects/desaturate.wgsl")
+            }
+            ImageEffectKind::Waves => include_str!("../renderer/shaders/image_effects/waves.wgsl"),
+            ImageEffectKind::Ripple => {
+                include_str!("../renderer/shaders/image_effects/ripple.wgsl")
+            }
+            ImageEffectKind::Scroll => {
+                include_str!("../renderer/shaders/image_effects/scroll.wgsl")
+            }
+            ImageEffectKind::HueShift => {
+                include_str!("../renderer/shaders/image_effects/hue_shift.wgsl")
+            }
+            ImageEffectKind::Pulse => include_str!("../renderer/shaders/image_effects/pulse.wgsl"),
+        }
+    }
+
+    /// `shader.wgsl` followed by the effect's fragment stage.
+    pub fn shader_source(&self) -> String {
+        format!(
+            "{}\n{}",
+            include_str!("../renderer/shaders/shader.wgsl"),
+            self.fragment_source()
+        )
+    }
+
+    /// The `effect` keys this effect reads, besides `name`.
+    pub fn parameters(&self) -> &'static [&'static str] {
+        match self {
+            ImageEffectKind::Tint => &["color", "amount"],
+            ImageEffectKind::Desaturate => &["amount"],
+            ImageEffectKind::Waves | ImageEffectKind::Ripple => {
+                &["amplitude", "frequency", "speed"]
+            }
+            ImageEffectKind::Scroll => &["velocity"],
+            ImageEffectKind::HueShift => &["degrees", "speed"],
+            ImageEffectKind::Pulse => &["amount", "speed"],
+        }
+    }
+
+    /// Whether the effect changes over time, so the image's uniform is rewritten every frame.
+    pub fn is_animated(&self) -> bool {
+        !matches!(self, ImageEffectKind::Tint | ImageEffectKind::Desaturate)
+    }
+
+    /// Packs the settings into the shader's `effect` uniform, filling in defaults for unset
+    /// keys.
+    pub fn params(&self, settings: &ImageEffectSettings) -> [f32; 4] {
+        let amount = |default| settings.amount.unwrap_or(default);
+        let speed = |default| settings.speed.unwrap_or(default);
+        match self {
+            ImageEffectKind::Tint => {
+                let [r, g, b] = settings.color.unwrap_or([1.0, 0.6, 0.3]);
+                [r, g, b, amount(1.0)]
+            }
+            ImageEffectKind::Desaturate => [amount(1.0), 0.0, 0.0, 0.0],
+            ImageEffectKind::Waves => [
+                settings.amplitude.unwrap_or(0.01),
+                settings.frequency.unwrap_or(8.0),
+                speed(0.5),
+                0.0,
+            ],
+            ImageEffectKind::Ripple => [
+                settings.amplitude.unwrap_or(0.01),
+                settings.frequency.unwrap_or(12.0),
+                speed(0.5),
+                0.0,
+            ],
+            ImageEffectKind::Scroll => {
+                let [x, y] = settings.velocity.unwrap_or([0.05, 0.0]);
+                [x, y, 0.0, 0.0]
+            }
+            ImageEffectKind::HueShift => [settings.degrees.unwrap_or(0.0), speed(30.0), 0.0, 0.0],
+            ImageEffectKind::Pulse => [amount(0.25), speed(1.0), 0.0, 0.0],
+        }
+    }
+}
+
+/// The effect of one image, as written in its `effect` table. Only the parameters the
+/// effect reads (see [`ImageEffectKind::parameters`]) may be set.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImageEffectSettings {
+    pub name: ImageEffectKind,
+    /// Tint color, RGB from 0 to 1.
+    #[serde(default)]
+    pub color: Option<[f32; 3]>,
+    /// Strength from 0 to 1.
+    #[serde(default)]
+    pub amount: Option<f32>,
+    /// Displacement as a fraction of the image's size.
+    #[serde(default)]
+    pub amplitude: Option<f32>,
+    /// Number of waves across the image.
+    #[serde(default)]
+    pub frequency: Option<f32>,
+    /// Cycles per second, or degrees per second for hue shifts.
+    #[serde(default)]
+    pub speed: Option<f32>,
+    /// Image sizes per second along x and y, y pointing down.
+    #[serde(default)]
+    pub velocity: Option<[f32; 2]>,
+    #[serde(default)]
+    pub degrees: Option<f32>,
+}
+
+impl ImageEffectSettings {
+    pub fn new(name: ImageEffectKind) -> Self {
+        ImageEffectSettings {
+            name,
+            color: None,
+            amount: None,
+            amplitude: None,
+            frequency: None,
+            speed: None,
+            velocity: None,
+            degrees: None,
+        }
+    }
+
+    /// Rejects parameters the effect doesn't read and values outside their range.
+    pub fn validate(&self, key: &str) -> anyhow::Result<()> {
+        let value = |value: Option<f32>| value.map(|value| vec![value]);
+        validation::validate_parameters(
+            key,
+            &format!("`{}`", self.name.name()),
+            self.name.parameters(),
+            vec![
+                ("color", self.color.map(Vec::from)),
+                ("amount", value(self.amount)),
+                ("amplitude", value(self.amplitude)),
+                ("frequency", value(self.frequency)),
+                ("speed", value(self.speed)),
+                ("velocity", self.velocity.map(Vec::from)),
+                ("degrees", value(self.degrees)),
+            ],
+        )?;
+
+        if self
+            .amount
+            .is_some_and(|amount| !(0.0..=1.0).contains(&amount))
+        {
+            bail!("`{key}.amount`: must be between 0.0 and 1.0");
+        }
+        if let Some(color) = self.color {
+            if color.iter().any(|c| !(0.0..=1.0).contains(c)) {
+                bail!("`{key}.color`: components must be between 0.0 and 1.0");
+            }
+        }
+        if self.frequency.is_some_and(|frequency| frequency < 0.0) {
+            bail!("`{key}.frequency`: must not be negative");
+        }
+        Ok(())
+    }
+}
+
+/// What sets an image's pipeline apart: its effect, if any, and its blend mode.
+type PipelineKey = (Option<ImageEffectKind>, BlendMode);
+
+/// Render pipelines for images, one per effect and blend mode, compiled the first time an
+/// image uses the combination and shared by every image using it afterwards. All of them
+/// share one pipeline layout and its bind group layouts, which images create their bind
+/// groups with.
+pub struct ImagePipelines {
+    format: TextureFormat,
+    texture_layout: wgpu::BindGroupLayout,
+    uniform_layout: wgpu::BindGroupLayout,
+    layout: wgpu::PipelineLayout,
+    pipelines: Mutex<HashMap<PipelineKey, Arc<RenderPipeline>>>,
+}
+
+impl ImagePipelines {
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        let texture_layout = Image::texture_bind_group_layout(device);
+        let uniform_layout = Image::uniform_bind_group_layout(device);
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Image render layout"),
+            bind_group_layouts: &[&texture_layout, &uniform_layout],
+            push_constant_ranges: &[],
+        });
+
+        ImagePipelines {
+            format,
+            texture_layout,
+            uniform_layout,
+            layout,
+            pipelines: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Layout of group 0 in `shader.wgsl`, see [`Image::texture_bind_group_layout`].
+    pub fn texture_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.texture_layout
+    }
+
+    /// Layout of group 1 in `shader.wgsl`, see [`Image::uniform_bind_group_layout`].
+    pub fn uniform_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.uniform_layout
+    }
+
+    /// The pipeline drawing images with `effect`, or `shader.wgsl`'s `fs_main` without one,
+    /// blended with `blend`. Created on first use.
+    pub fn get(
+        &self,
+        device: &Device,
+        effect: Option<ImageEffectKind>,
+        blend: BlendMode,
+    ) -> Arc<RenderPipeline> {
+        let mut pipelines = self.pipelines.lock().unwrap();
+        pipelines
+            .entry((effect, blend))
+            .or_insert_with(|| Arc::new(self.create_pipeline(device, effect, blend)))
+            .clone()
+    }
+
+    fn create_pipeline(
+        &self,
+        device: &Device,
+        effect: Option<ImageEffectKind>,
+        blend: BlendMode,
+    ) -> RenderPipeline {
+        let (label, source, entry_point) = match effect {
+            Some(kind) => (kind.name(), kind.shader_source(), "fs_effect"),
+            None => (
+                "shader.wgsl",
+                include_str!("../renderer/shaders/shader.wgsl").to_string(),
+                "fs_main",
+            ),
+        };
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Image pipeline"),
+            layout: Some(&self.layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: self.format,
+                    blend: Some(blend.state()),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    }
+}
@@ -0,0 +1,427 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use anyhow::Context;
+use cgmath::{Deg, Vector2};
+use image::{DynamicImage, Frame};
+use wgpu::{Device, Queue, SurfaceConfiguration};
+
+use crate::manifest::{GifManifest, ImageManifest, Manifest, SceneManifest, VideoManifest};
+
+use super::{
+    animated::{self, FrameStream, Playback},
+    animation::Animator,
+    image_effects::{ImageEffectKind, ImagePipelines},
+    primitives::{
+        image::{Image, Placement},
+        plane::FitMode,
+        transform::Transform,
+    },
+    scenes::{
+        gif_scene::{GifFrames, GifScene},
+        image_scene::ImageScene,
+        parallax_scene::ParallaxScene,
+        scene::SceneType,
+        schedule_scene::ScheduleScene,
+        slideshow_scene::SlideshowScene,
+        test_image_scene::TestImageScene,
+        video_scene::VideoScene,
+    },
+    texture::Texture,
+    transitions::TransitionPipelines,
+    video::{VideoDecoder, VideoInfo},
+};
+
+/// Builds the scene described by a manifest, uploading all of its assets to the GPU. Images
+/// are fitted to the output size in `config`. Scenes switching between scenes of their own
+/// render their transitions at `transition_quality`.
+pub fn load_scene(
+    device: &Device,
+    queue: &Queue,
+    config: &SurfaceConfiguration,
+    pipelines: &Arc<ImagePipelines>,
+    transition_pipelines: &Arc<TransitionPipelines>,
+    manifest: &Manifest,
+    transition_quality: f32,
+) -> anyhow::Result<SceneType> {
+    let [r, g, b, a] = manifest.clear_color;
+    let clear_color = wgpu::Color { r, g, b, a };
+
+    load_scene_manifest(
+        device,
+        queue,
+        config,
+        pipelines,
+        transition_pipelines,
+        &manifest.scene,
+        "scene",
+        clear_color,
+        transition_quality,
+    )
+}
+
+/// Builds a scene found at `key` in the manifest, used in error messages.
+#[allow(clippy::too_many_arguments)]
+pub fn load_scene_manifest(
+    device: &Device,
+    queue: &Queue,
+    config: &SurfaceConfiguration,
+    pipelines: &Arc<ImagePipelines>,
+    transition_pipelines: &Arc<TransitionPipelines>,
+    scene: &SceneManifest,
+    key: &str,
+    clear_color: wgpu::Color,
+    transition_quality: f32,
+) -> anyhow::Result<SceneType> {
+    let scene = match scene {
+        SceneManifest::Image(image) => SceneType::Image(ImageScene {
+            image: load_image(device, queue, config, pipelines, image)
+                .with_context(|| format!("`{key}.image`"))?,
+            clear_color,
+        }),
+        SceneManifest::Images(images) => {
+            let images = images
+                .iter()
+                .enumerate()
+                .map(|(i, image)| {
+                    load_image(device, queue, config, pipelines, image)
+                        .with_context(|| format!("`{key}.images[{i}]`"))
+                })
+                .collect::<anyhow::Result<Vec<Image>>>()?;
+
+            SceneType::TestImages(TestImageScene {
+                images,
+                clear_color,
+            })
+        }
+        SceneManifest::Gif(gif) => SceneType::Gif(
+            load_gif(device, queue, config, pipelines, gif, clear_color)
+                .with_context(|| format!("`{key}.gif`"))?,
+        ),
+        SceneManifest::Video(video) => SceneType::Video(
+            load_video(device, config, pipelines, video, clear_color)
+                .with_context(|| format!("`{key}.video`"))?,
+        ),
+        SceneManifest::Slideshow(slideshow) => SceneType::Slideshow(
+            SlideshowScene::new(
+                device,
+                queue,
+                config,
+                pipelines,
+                transition_pipelines,
+                slideshow,
+                &format!("{key}.slideshow"),
+                clear_color,
+                transition_quality,
+            )
+            .with_context(|| format!("`{key}.slideshow`"))?,
+        ),
+        SceneManifest::Schedule(schedule) => SceneType::Schedule(ScheduleScene::new(
+            config,
+            pipelines,
+            transition_pipelines,
+            schedule,
+            &format!("{key}.schedule"),
+            clear_color,
+            transition_quality,
+        )),
+        SceneManifest::Parallax(parallax) => {
+            let images = parallax
+                .layers
+                .iter()
+                .enumerate()
+                .map(|(i, layer)| {
+                    load_image(device, queue, config, pipelines, &layer.image)
+                        .with_context(|| format!("`{key}.parallax.layers[{i}]`"))
+                })
+                .collect::<anyhow::Result<Vec<Image>>>()?;
+
+            SceneType::Parallax(ParallaxScene::new(images, parallax, clear_color))
+        }
+    };
+
+    Ok(scene)
+}
+
+pub fn load_image(
+    device: &Device,
+    queue: &Queue,
+    config: &SurfaceConfiguration,
+    pipelines: &ImagePipelines,
+    manifest: &ImageManifest,
+) -> anyhow::Result<Image> {
+    let decoded = decode_image(&manifest.path)?;
+    upload_image(device, queue, config, pipelines, manifest, &decoded)
+}
+
+/// Reads and decodes an image.
+pub fn decode_image(path: &Path) -> anyhow::Result<DynamicImage> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+    image::load_from_memory(&bytes)
+        .with_context(|| format!("failed to decode `{}`", path.display()))
+}
+
+/// Uploads an image from [`decode_image`] and places it as `manifest` says.
+pub fn upload_image(
+    device: &Device,
+    queue: &Queue,
+    config: &SurfaceConfiguration,
+    pipelines: &ImagePipelines,
+    manifest: &ImageManifest,
+    decoded: &DynamicImage,
+) -> anyhow::Result<Image> {
+    let texture = Texture::from_image(device, queue, decoded, Some("image"))?;
+
+    let mut image = create_image(device, config, pipelines, manifest, texture);
+    image.set_animator(manifest.animation.clone().map(Animator::new));
+    Ok(image)
+}
+
+/// Frames of an animation decoded ahead of uploading them.
+pub struct DecodedAnimation {
+    /// Every frame, or only the first if they went over the memory budget.
+    pub frames: Vec<Frame>,
+    /// The frames went over the memory budget, so the rest are streamed while playing.
+    pub streamed: bool,
+}
+
+/// Decodes every frame upfront if they fit the gif's memory budget, otherwise starts
+/// streaming them from a decoder thread.
+pub fn load_gif(
+    device: &Device,
+    queue: &Queue,
+    config: &SurfaceConfiguration,
+    pipelines: &ImagePipelines,
+    manifest: &GifManifest,
+    clear_color: wgpu::Color,
+) -> anyhow::Result<GifScene> {
+    let decoded = decode_animation(&manifest.image.path, manifest.memory_budget)?;
+    upload_gif(
+        device,
+        queue,
+        config,
+        pipelines,
+        manifest,
+        decoded,
+        clear_color,
+    )
+}
+
+/// Decodes an animation's frames within `memory_budget` megabytes, see
+/// [`GifManifest::memory_budget`].
+pub fn decode_animation(
+    path: &Path,
+    memory_budget: Option<u32>,
+) -> anyhow::Result<DecodedAnimation> {
+    let budget = animated::memory_budget(memory_budget);
+
+    let decode_error = || format!("failed to decode frames of `{}`", path.display());
+    let mut decoder = animated::decode_frames(path)?;
+    let first = decoder
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("`{}` contains no frames", path.display()))?
+        .with_context(decode_error)?;
+
+    // Every frame has the canvas size, so the first tells what all of them would take
+    let count = animated::frame_count(path)?;
+    if count > 1 && first.buffer().len() as u64 * count as u64 > budget {
+        return Ok(DecodedAnimation {
+            frames: vec![first],
+            streamed: true,
+        });
+    }
+
+    let mut frames = vec![first];
+    for frame in decoder {
+        frames.push(frame.with_context(decode_error)?);
+    }
+    Ok(DecodedAnimation {
+        frames,
+        streamed: false,
+    })
+}
+
+/// Uploads an animation from [`decode_animation`], starting its decoder thread if it is
+/// streamed.
+pub fn upload_gif(
+    device: &Device,
+    queue: &Queue,
+    config: &SurfaceConfiguration,
+    pipelines: &ImagePipelines,
+    manifest: &GifManifest,
+    decoded: DecodedAnimation,
+    clear_color: wgpu::Color,
+) -> anyhow::Result<GifScene> {
+    let first = &decoded.frames[0];
+    let (width, height) = first.buffer().dimensions();
+    let texture = Texture::empty(device, width, height, Some("animation frame"));
+    texture.write(queue, first.buffer());
+    let mut image = create_image(device, config, pipelines, &manifest.image, texture);
+    image.set_animator(manifest.image.animation.clone().map(Animator::new));
+
+    let frames = if decoded.streamed {
+        if manifest.reverse {
+            log::warn!(
+                "`{}` is over its memory budget, so it plays forwards despite `reverse`",
+                manifest.image.path.display()
+            );
+        }
+        GifFrames::Streamed {
+            next_frame_at: animated::frame_delay(first),
+            stream: FrameStream::spawn(&manifest.image.path, 1)?,
+        }
+    } else {
+        GifFrames::cached(decoded.frames)
+    };
+
+    let mut scene = GifScene::new(image, frames, clear_color);
+    // Nothing has played yet, so neither needs to keep a position
+    scene.playback = Playback::new(manifest.speed);
+    scene.reverse = manifest.reverse && !decoded.streamed;
+    Ok(scene)
+}
+
+/// A slideshow's slide decoded ahead of being shown.
+pub enum DecodedSlide {
+    Image(DynamicImage),
+    Animation(DecodedAnimation),
+}
+
+/// Decodes an animation or a still image, told apart by the file's contents. Needs no GPU,
+/// so slideshows run it on a background thread.
+pub fn decode_slide(path: &Path) -> anyhow::Result<DecodedSlide> {
+    if animated::is_animated(path) {
+        decode_animation(path, None).map(DecodedSlide::Animation)
+    } else {
+        decode_image(path).map(DecodedSlide::Image)
+    }
+}
+
+/// Uploads a slide from [`decode_slide`] as a scene of its own.
+pub fn upload_slide(
+    device: &Device,
+    queue: &Queue,
+    config: &SurfaceConfiguration,
+    pipelines: &ImagePipelines,
+    manifest: ImageManifest,
+    decoded: DecodedSlide,
+    clear_color: wgpu::Color,
+) -> anyhow::Result<SceneType> {
+    let scene = match decoded {
+        DecodedSlide::Image(decoded) => SceneType::Image(ImageScene {
+            image: upload_image(device, queue, config, pipelines, &manifest, &decoded)?,
+            clear_color,
+        }),
+        DecodedSlide::Animation(decoded) => SceneType::Gif(upload_gif(
+            device,
+            queue,
+            config,
+            pipelines,
+            &GifManifest::new(manifest),
+            decoded,
+            clear_color,
+        )?),
+    };
+    Ok(scene)
+}
+
+/// Probes the video and starts decoding it. Frames are uploaded as the scene plays.
+pub fn load_video(
+    device: &Device,
+    config: &SurfaceConfiguration,
+    pipelines: &ImagePipelines,
+    manifest: &VideoManifest,
+    clear_color: wgpu::Color,
+) -> anyhow::Result<VideoScene> {
+    let path = &manifest.image.path;
+    let info = VideoInfo::probe(path)?;
+    let start = Duration::from_secs_f32(manifest.start);
+    let decoder = VideoDecoder::spawn(path, info, start, manifest.looping)?;
+
+    let texture = Texture::empty(device, info.width, info.height, Some("video frame"));
+    let mut image = create_image(device, config, pipelines, &manifest.image, texture);
+    image.set_animator(manifest.image.animation.clone().map(Animator::new));
+
+    Ok(VideoScene::new(
+        image,
+        decoder,
+        clear_color,
+        manifest.looping,
+        manifest.speed,
+        start,
+    ))
+}
+
+/// Places a texture as the manifest describes and creates its GPU buffers.
+fn create_image(
+    device: &Device,
+    config: &SurfaceConfiguration,
+    pipelines: &ImagePipelines,
+    manifest: &ImageManifest,
+    mut texture: Texture,
+) -> Image {
+    // Scrolled images wrap around
+    let scrolled = manifest
+        .effect
+        .as_ref()
+        .is_some_and(|effect| effect.name == ImageEffectKind::Scroll);
+    if manifest.fit == FitMode::Tile || scrolled {
+        texture.set_address_mode(device, wgpu::AddressMode::Repeat);
+    }
+
+    // Tiles take the scale themselves, so the tiled plane keeps covering the output
+    let tiled = manifest.fit == FitMode::Tile;
+    let [scale_x, scale_y] = manifest.scale;
+    let placement = Placement {
+        fit: manifest.fit,
+        tile_scale: if tiled { scale_x } else { 1.0 },
+    };
+    let transform = Transform {
+        translation: manifest.offset.into(),
+        rotation: Deg(manifest.rotation),
+        scale: if tiled {
+            Vector2::new(1.0, 1.0)
+        } else {
+            Vector2::new(scale_x, scale_y)
+        },
+        anchor: manifest.anchor.into(),
+        z_index: manifest.z_index,
+    };
+    let mut image = Image::new(texture, placement, transform, (config.width, config.height));
+    image.set_appearance(manifest.opacity, manifest.tint);
+    let effect = manifest
+        .effect
+        .as_ref()
+        .map(|effect| (effect.name, effect.name.params(effect)));
+    image.set_shading(device, pipelines, manifest.blend, effect);
+    image.create_buffers(device, pipelines);
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::animated::tests::{gif, TempFile};
+
+    #[test]
+    fn animations_over_the_budget_are_streamed() {
+        // Three frames of 400x400 RGBA take about 1.8 MB decoded
+        let file = TempFile::new("budget.gif", &gif(3, 400, 100));
+
+        let decoded = decode_animation(&file.0, Some(1)).unwrap();
+        assert!(decoded.streamed);
+        assert_eq!(decoded.frames.len(), 1);
+
+        let decoded = decode_animation(&file.0, Some(2)).unwrap();
+        assert!(!decoded.streamed);
+        assert_eq!(decoded.frames.len(), 3);
+    }
+
+    #[test]
+    fn single_frames_are_never_streamed() {
+        let file = TempFile::new("single.gif", &gif(1, 800, 100));
+        let decoded = decode_animation(&file.0, Some(1)).unwrap();
+        assert!(!decoded.streamed);
+        assert_eq!(decoded.frames.len(), 1);
+    }
+}
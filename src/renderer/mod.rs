@@ -1,5 +1,15 @@
+pub mod animated;
+pub mod animation;
+pub mod clock;
+pub mod effects;
 pub mod engine;
+pub mod image_effects;
+pub mod loader;
 pub mod primitives;
 pub mod scenes;
+pub mod schedule;
 pub mod texture;
+pub mod timeline;
 pub mod transitions;
+pub mod validation;
+pub mod video;
@@ -1,50 +1,343 @@
-use wgpu::{util::DeviceExt, Device};
+use std::{sync::Arc, time::Duration};
 
-use crate::renderer::texture::Texture;
+use cgmath::{Deg, Vector2};
+use serde::Deserialize;
+use wgpu::{util::DeviceExt, Device, Queue, RenderPipeline};
 
-use super::plane::Plane;
+use crate::renderer::{
+    animation::{Animator, Properties},
+    image_effects::{ImageEffectKind, ImagePipelines},
+    texture::Texture,
+};
+
+use super::{
+    plane::{FitMode, Plane},
+    transform::Transform,
+};
+
+/// How an image is fitted to the output, kept so its plane can be rebuilt on resize.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub fit: FitMode,
+    /// Size of the tiles relative to the image for [`FitMode::Tile`], ignored otherwise.
+    pub tile_scale: f32,
+}
+
+/// How an image is combined with what is drawn below it. Every pipeline blends
+/// premultiplied colors, `shader.wgsl` premultiplies them unless the image already is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlendMode {
+    /// Drawn over what is below, see-through where transparent.
+    #[default]
+    Alpha,
+    /// Adds its colors, brightening what is below.
+    Additive,
+    /// Multiplies its colors, darkening what is below.
+    Multiply,
+    /// The inverse of multiplying the inverted colors, brightening what is below.
+    Screen,
+    /// Like `alpha`, for images whose colors are already multiplied by their alpha.
+    Premultiplied,
+}
+
+impl BlendMode {
+    pub fn state(&self) -> wgpu::BlendState {
+        let color = |src_factor, dst_factor| wgpu::BlendComponent {
+            src_factor,
+            dst_factor,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let color = match self {
+            BlendMode::Alpha | BlendMode::Premultiplied => {
+                return wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING;
+            }
+            BlendMode::Additive => color(wgpu::BlendFactor::One, wgpu::BlendFactor::One),
+            BlendMode::Multiply => {
+                color(wgpu::BlendFactor::Dst, wgpu::BlendFactor::OneMinusSrcAlpha)
+            }
+            BlendMode::Screen => color(wgpu::BlendFactor::One, wgpu::BlendFactor::OneMinusSrc),
+        };
+        wgpu::BlendState {
+            color,
+            alpha: wgpu::BlendComponent::OVER,
+        }
+    }
+}
+
+/// Per-image values read by `shader.wgsl` from group 1.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ImageUniform {
+    pub model: [[f32; 4]; 4],
+    /// RGB multiplied with the image, alpha is the opacity.
+    pub tint: [f32; 4],
+    /// Parameters of the image's effect, see [`ImageEffectKind::params`].
+    pub effect: [f32; 4],
+    /// Clock time in seconds, for animated effects.
+    pub time: f32,
+    /// 1.0 if the texture's colors are already premultiplied, see [`BlendMode::Premultiplied`].
+    pub premultiplied: f32,
+    pub _padding: [f32; 2],
+}
 
 pub struct Image {
-    pub plane: Plane,
-    pub texture: Texture,
-    pub bind_group: Option<wgpu::BindGroup>,
-    pub vertex_buffer: Option<wgpu::Buffer>,
-    pub index_buffer: Option<wgpu::Buffer>,
+    plane: Plane,
+    texture: Texture,
+    placement: Placement,
+    transform: Transform,
+    /// From 0, invisible, to 1.
+    opacity: f32,
+    /// Multiplied with the image's colors.
+    tint: [f32; 3],
+    blend: BlendMode,
+    animator: Option<Animator>,
+    /// Effect drawing the image instead of `shader.wgsl`'s `fs_main`, with its parameters.
+    effect: Option<(ImageEffectKind, [f32; 4])>,
+    /// Pipeline of `effect` and `blend`, shared with the other images using both. Scenes
+    /// fall back to the engine's pipeline without one.
+    pipeline: Option<Arc<RenderPipeline>>,
+    /// Seconds passed to the effect, set by [`animate`](Self::animate).
+    time: f32,
+    /// Width / height of the output the plane was fitted to.
+    aspect: f32,
+    bind_group: Option<wgpu::BindGroup>,
+    vertex_buffer: Option<wgpu::Buffer>,
+    index_buffer: Option<wgpu::Buffer>,
+    uniform_buffer: Option<wgpu::Buffer>,
+    uniform_bind_group: Option<wgpu::BindGroup>,
+    /// The uniform buffer lags behind the plane after a resize.
+    uniform_outdated: bool,
 }
 
 impl Image {
-    pub fn test(device: &Device, queue: &wgpu::Queue, scale: f32, x: f32, y: f32) -> Self {
+    pub fn new(
+        texture: Texture,
+        placement: Placement,
+        transform: Transform,
+        output_size: (u32, u32),
+    ) -> Self {
         Image {
-            plane: Plane::new_with_offset(scale, x, y),
-            texture: Texture::from_bytes(
-                device,
-                queue,
-                include_bytes!("../../../images/1.png"),
-                "label",
-            )
-            .unwrap(),
+            plane: Self::plane(&texture, &placement, output_size),
+            texture,
+            placement,
+            transform,
+            opacity: 1.0,
+            tint: [1.0, 1.0, 1.0],
+            blend: BlendMode::Alpha,
+            animator: None,
+            effect: None,
+            pipeline: None,
+            time: 0.0,
+            aspect: output_size.0.max(1) as f32 / output_size.1.max(1) as f32,
             bind_group: None,
             vertex_buffer: None,
             index_buffer: None,
+            uniform_buffer: None,
+            uniform_bind_group: None,
+            uniform_outdated: false,
         }
     }
 
-    pub fn test_gif(
+    pub fn set_appearance(&mut self, opacity: f32, tint: [f32; 3]) {
+        self.opacity = opacity;
+        self.tint = tint;
+    }
+
+    /// Draws the image with `blend` and `effect`, a kind with its parameters, through the
+    /// pipeline `pipelines` holds for both. Images without either use the scene's pipeline.
+    pub fn set_shading(
+        &mut self,
         device: &Device,
-        queue: &wgpu::Queue,
-        scale: f32,
-        bytes: &image::Frame,
-    ) -> Self {
-        Image {
-            plane: Plane::new(scale),
-            texture: Texture::from_frame(device, queue, bytes, Some("label")).unwrap(),
-            bind_group: None,
-            vertex_buffer: None,
-            index_buffer: None,
+        pipelines: &ImagePipelines,
+        blend: BlendMode,
+        effect: Option<(ImageEffectKind, [f32; 4])>,
+    ) {
+        self.blend = blend;
+        self.effect = effect;
+        self.pipeline = (effect.is_some() || blend != BlendMode::Alpha)
+            .then(|| pipelines.get(device, effect.map(|(kind, _)| kind), blend));
+    }
+
+    pub fn set_animator(&mut self, animator: Option<Animator>) {
+        self.animator = animator;
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    /// Moves the image. Call [`write_uniform`](Self::write_uniform) afterwards.
+    pub fn transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
+    /// Draws the image into `render_pass`, through its own pipeline or else `pipeline`.
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        pipeline: &'a RenderPipeline,
+    ) {
+        render_pass.set_pipeline(self.pipeline.as_deref().unwrap_or(pipeline));
+        render_pass.set_bind_group(0, self.bind_group.as_ref().unwrap(), &[]);
+        render_pass.set_bind_group(1, self.uniform_bind_group.as_ref().unwrap(), &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.as_ref().unwrap().slice(..));
+        render_pass.set_index_buffer(
+            self.index_buffer.as_ref().unwrap().slice(..),
+            wgpu::IndexFormat::Uint16,
+        );
+        render_pass.draw_indexed(0..self.plane.get_indices().len() as u32, 0, 0..1);
+    }
+
+    /// Creates the GPU buffers and bind groups needed to draw the image, the latter with
+    /// the layouts `pipelines` share.
+    pub fn create_buffers(&mut self, device: &Device, pipelines: &ImagePipelines) {
+        self.create_bind_group(device, pipelines.texture_layout());
+        self.create_index_buffer(device);
+        self.create_vertex_buffer(device);
+        self.create_uniform_buffer(device, pipelines.uniform_layout());
+    }
+
+    fn plane(texture: &Texture, placement: &Placement, output_size: (u32, u32)) -> Plane {
+        Plane::fitted(
+            placement.fit,
+            placement.tile_scale,
+            (texture.texture.width(), texture.texture.height()),
+            output_size,
+        )
+    }
+
+    /// Refits the plane to a new output size.
+    pub fn resize(&mut self, device: &Device, output_size: (u32, u32)) {
+        self.plane = Self::plane(&self.texture, &self.placement, output_size);
+        self.aspect = output_size.0.max(1) as f32 / output_size.1.max(1) as f32;
+        self.create_vertex_buffer(device);
+        // The uniform holds the plane's bounds, rewritten on the next update
+        self.uniform_outdated = true;
+    }
+
+    fn uniform(&self) -> ImageUniform {
+        let positions = self.plane.vertices.iter().map(|v| v.position);
+        let (min, max) = positions.fold(
+            (
+                Vector2::new(f32::MAX, f32::MAX),
+                Vector2::new(f32::MIN, f32::MIN),
+            ),
+            |(min, max), [x, y, _]| {
+                (
+                    Vector2::new(min.x.min(x), min.y.min(y)),
+                    Vector2::new(max.x.max(x), max.y.max(y)),
+                )
+            },
+        );
+        let [r, g, b] = self.tint;
+        ImageUniform {
+            model: self.transform.matrix(min, max, self.aspect).into(),
+            tint: [r, g, b, self.opacity],
+            effect: self.effect.map_or([0.0; 4], |(_, params)| params),
+            time: self.time,
+            premultiplied: if self.blend == BlendMode::Premultiplied {
+                1.0
+            } else {
+                0.0
+            },
+            _padding: [0.0; 2],
+        }
+    }
+
+    /// Uploads `transform`, `opacity`, `tint` and `time` after they changed.
+    pub fn write_uniform(&mut self, queue: &Queue) {
+        self.uniform_outdated = false;
+        if let Some(buffer) = &self.uniform_buffer {
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&[self.uniform()]));
+        }
+    }
+
+    /// Applies the animation's current values and advances an animated effect, if the image
+    /// has either, and uploads the uniform if that or a resize changed it.
+    pub fn animate(&mut self, queue: &Queue, now: Duration) {
+        let animated_effect = self.effect.is_some_and(|(kind, _)| kind.is_animated());
+        if self.animator.is_none() && !animated_effect && !self.uniform_outdated {
+            return;
+        }
+
+        self.advance(now);
+        self.write_uniform(queue);
+    }
+
+    /// Like [`animate`](Self::animate), but leaves uploading the uniform to the caller, for
+    /// changes made on top of the animation. Call [`write_uniform`](Self::write_uniform)
+    /// afterwards.
+    pub fn advance(&mut self, now: Duration) {
+        if let Some(animator) = &mut self.animator {
+            let properties = animator.sample(now);
+            self.apply(&properties);
+        }
+        self.time = now.as_secs_f32();
+    }
+
+    /// Overrides the properties that are animated. Call [`write_uniform`](Self::write_uniform)
+    /// afterwards.
+    pub fn apply(&mut self, properties: &Properties) {
+        if let Some(offset) = properties.offset {
+            self.transform.translation = offset.into();
+        }
+        if let Some(scale) = properties.scale {
+            self.transform.scale = scale.into();
+        }
+        if let Some(rotation) = properties.rotation {
+            self.transform.rotation = Deg(rotation);
+        }
+        if let Some(opacity) = properties.opacity {
+            self.opacity = opacity;
+        }
+        if let Some(tint) = properties.tint {
+            self.tint = tint;
         }
     }
 
-    pub fn create_vertex_buffer(&mut self, device: &Device) {
+    /// Layout of group 1 in `shader.wgsl`: the [`ImageUniform`].
+    pub fn uniform_bind_group_layout(device: &Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("image_uniform_bind_group_layout"),
+        })
+    }
+
+    fn create_uniform_buffer(&mut self, device: &Device, layout: &wgpu::BindGroupLayout) {
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Image uniform buffer"),
+            contents: bytemuck::cast_slice(&[self.uniform()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("image_uniform_bind_group"),
+        });
+
+        self.uniform_buffer = Some(uniform_buffer);
+        self.uniform_bind_group = Some(uniform_bind_group);
+    }
+
+    fn create_vertex_buffer(&mut self, device: &Device) {
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Image vertex buffer"),
             contents: bytemuck::cast_slice(&self.plane.vertices),
@@ -53,7 +346,7 @@ impl Image {
         self.vertex_buffer = Some(vertex_buffer);
     }
 
-    pub fn create_index_buffer(&mut self, device: &Device) {
+    fn create_index_buffer(&mut self, device: &Device) {
         let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Image index buffer"),
             contents: bytemuck::cast_slice(&self.plane.get_indices()),
@@ -62,34 +355,36 @@ impl Image {
         self.index_buffer = Some(index_buffer);
     }
 
-    pub fn create_bind_group(&mut self, device: &Device) {
-        let texture_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            multisampled: false,
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        // This should match the filterable field of the
-                        // corresponding Texture entry above.
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
+    /// Layout of group 0 in `shader.wgsl`: the texture and its sampler.
+    pub fn texture_bind_group_layout(device: &Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
                     },
-                ],
-                label: Some("texture_bind_group_layout"),
-            });
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    // This should match the filterable field of the
+                    // corresponding Texture entry above.
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("texture_bind_group_layout"),
+        })
+    }
 
+    fn create_bind_group(&mut self, device: &Device, layout: &wgpu::BindGroupLayout) {
         let diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &texture_bind_group_layout,
+            layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
@@ -1,3 +1,4 @@
 pub mod image;
 pub mod plane;
+pub mod transform;
 pub mod vertex;
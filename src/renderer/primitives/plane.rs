@@ -1,15 +1,29 @@
+use serde::Deserialize;
+
 use super::vertex::Vertex;
 
+/// How an image is fitted to the output when their aspect ratios differ.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FitMode {
+    /// Fill the output, ignoring the image's aspect ratio.
+    #[default]
+    Stretch,
+    /// Fill the output, cropping whatever overflows.
+    Cover,
+    /// Show the whole image, leaving bars of the clear color.
+    Contain,
+    /// Native size in pixels, centered.
+    Center,
+    /// Repeat the image at native size from the top left.
+    Tile,
+}
+
 #[derive(Debug, Clone)]
 pub struct Plane {
     pub vertices: Vec<Vertex>,
 }
 
-trait PlaneVertex {
-    fn move_x_position(&mut self, x: f32) -> Self;
-    fn move_y_position(&mut self, y: f32) -> Self;
-}
-
 impl Plane {
     pub fn new(scale: f32) -> Self {
         let vertices = vec![
@@ -33,48 +47,59 @@ impl Plane {
         Plane { vertices }
     }
 
-    pub fn new_with_offset(scale: f32, x_offset: f32, y_offset: f32) -> Self {
+    /// A quad showing an image of `image_size` pixels on an output of `output_size` pixels
+    /// according to `fit`. For [`FitMode::Tile`], `tile_scale` scales the tiles, and the
+    /// texture's sampler must repeat.
+    pub fn fitted(
+        fit: FitMode,
+        tile_scale: f32,
+        image_size: (u32, u32),
+        output_size: (u32, u32),
+    ) -> Self {
+        let (image_width, image_height) = (image_size.0.max(1) as f32, image_size.1.max(1) as f32);
+        let (output_width, output_height) =
+            (output_size.0.max(1) as f32, output_size.1.max(1) as f32);
+        let image_aspect = image_width / image_height;
+        let output_aspect = output_width / output_height;
+
+        // Half the quad's size in clip space, where the output spans -1..1
+        let (half_width, half_height) = match fit {
+            FitMode::Stretch | FitMode::Tile => (1.0, 1.0),
+            FitMode::Cover if image_aspect > output_aspect => (image_aspect / output_aspect, 1.0),
+            FitMode::Cover => (1.0, output_aspect / image_aspect),
+            FitMode::Contain if image_aspect > output_aspect => (1.0, output_aspect / image_aspect),
+            FitMode::Contain => (image_aspect / output_aspect, 1.0),
+            FitMode::Center => (image_width / output_width, image_height / output_height),
+        };
+        let (u, v) = match fit {
+            FitMode::Tile => (
+                output_width / (image_width * tile_scale),
+                output_height / (image_height * tile_scale),
+            ),
+            _ => (1.0, 1.0),
+        };
+
         let vertices = vec![
             Vertex {
-                position: [-scale + x_offset, -scale + y_offset, 0.0],
-                tex_coords: [0.0, 1.0],
+                position: [-half_width, -half_height, 0.0],
+                tex_coords: [0.0, v],
             },
             Vertex {
-                position: [scale + x_offset, -scale + y_offset, 0.0],
-                tex_coords: [1.0, 1.0],
+                position: [half_width, -half_height, 0.0],
+                tex_coords: [u, v],
             },
             Vertex {
-                position: [scale + x_offset, scale + y_offset, 0.0],
-                tex_coords: [1.0, 0.0],
+                position: [half_width, half_height, 0.0],
+                tex_coords: [u, 0.0],
             },
             Vertex {
-                position: [-scale + x_offset, scale + y_offset, 0.0],
+                position: [-half_width, half_height, 0.0],
                 tex_coords: [0.0, 0.0],
             },
         ];
         Plane { vertices }
     }
 
-    #[allow(dead_code)]
-    pub fn move_x_offset(&mut self, x_offset: f32) -> &mut Self {
-        self.vertices = self
-            .vertices
-            .iter_mut()
-            .map(|v| v.move_x_position(x_offset))
-            .collect::<Vec<Vertex>>();
-        self
-    }
-
-    #[allow(dead_code)]
-    pub fn move_y_offset(&mut self, y_offset: f32) -> &mut Self {
-        self.vertices = self
-            .vertices
-            .iter_mut()
-            .map(|v| v.move_y_position(y_offset))
-            .collect::<Vec<Vertex>>();
-        self
-    }
-
     #[allow(dead_code)]
     pub fn get_vertices(&self) -> Vec<Vertex> {
         self.vertices.clone()
@@ -84,15 +109,3 @@ impl Plane {
         vec![0, 1, 2, 0, 2, 3]
     }
 }
-
-impl PlaneVertex for Vertex {
-    fn move_x_position(&mut self, x: f32) -> Self {
-        self.position[0] += x;
-        *self
-    }
-
-    fn move_y_position(&mut self, y: f32) -> Self {
-        self.position[1] += y;
-        *self
-    }
-}
@@ -0,0 +1,51 @@
+use cgmath::{Deg, Matrix4, Vector2, Vector3};
+
+/// Placement of an image on top of its fitted plane, applied in the vertex shader so it can
+/// change every frame without rebuilding vertex buffers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    /// Offset in clip space, where the output spans -1..1 on both axes and y points up.
+    pub translation: Vector2<f32>,
+    /// Clockwise rotation around the anchor.
+    pub rotation: Deg<f32>,
+    /// Scale around the anchor, per axis.
+    pub scale: Vector2<f32>,
+    /// Point of the image that stays in place when rotating and scaling, from (0, 0) at its
+    /// top left to (1, 1) at its bottom right.
+    pub anchor: Vector2<f32>,
+    /// Images with a higher index are drawn on top of lower ones.
+    pub z_index: i32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            translation: Vector2::new(0.0, 0.0),
+            rotation: Deg(0.0),
+            scale: Vector2::new(1.0, 1.0),
+            anchor: Vector2::new(0.5, 0.5),
+            z_index: 0,
+        }
+    }
+}
+
+impl Transform {
+    /// The model matrix for a plane spanning `min` to `max` in clip space on an output with
+    /// the given width / height `aspect`. Rotation happens in pixel proportions, so images
+    /// don't shear on wide outputs.
+    pub fn matrix(&self, min: Vector2<f32>, max: Vector2<f32>, aspect: f32) -> Matrix4<f32> {
+        let anchor = Vector3::new(
+            (min.x + self.anchor.x * (max.x - min.x)) * aspect,
+            max.y - self.anchor.y * (max.y - min.y),
+            0.0,
+        );
+
+        Matrix4::from_translation(self.translation.extend(0.0))
+            * Matrix4::from_nonuniform_scale(1.0 / aspect, 1.0, 1.0)
+            * Matrix4::from_translation(anchor)
+            * Matrix4::from_angle_z(-self.rotation)
+            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, 1.0)
+            * Matrix4::from_translation(-anchor)
+            * Matrix4::from_nonuniform_scale(aspect, 1.0, 1.0)
+    }
+}
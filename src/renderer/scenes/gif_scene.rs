@@ -1,89 +1,152 @@
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
-use wgpu::{CommandEncoder, RenderPipeline, TextureView};
+use image::Frame;
+use wgpu::{CommandEncoder, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureView};
 
-use crate::renderer::primitives::image::Image;
+use crate::renderer::{
+    animated::{frame_at, frame_delay, FrameStream, Playback},
+    clock::Clock,
+    primitives::image::Image,
+};
 
 use super::scene::Scene;
 
+/// Where the frames of a [`GifScene`] come from.
+pub enum GifFrames {
+    /// Every frame decoded upfront, for animations within their memory budget.
+    Cached {
+        frames: Vec<Frame>,
+        /// Sum of the frames' delays.
+        duration: Duration,
+    },
+    /// Frames decoded while playing, for animations over their memory budget.
+    Streamed {
+        stream: FrameStream,
+        /// Playback time at which the frame on screen ends.
+        next_frame_at: Duration,
+    },
+}
+
+impl GifFrames {
+    pub fn cached(frames: Vec<Frame>) -> Self {
+        let duration = frames.iter().map(frame_delay).sum();
+        GifFrames::Cached { frames, duration }
+    }
+}
+
+/// Plays an animated gif, png or webp by writing its frames into a single texture as they
+/// come up.
 pub struct GifScene {
-    pub first_load: bool,
-    pub time_loaded: Option<Instant>,
-    pub time_till_next_frame: u128,
-    pub current_frame: u32,
-    pub frames: Vec<(Image, Duration)>,
+    pub(crate) image: Image,
+    pub(crate) frames: GifFrames,
+    pub(crate) playback: Playback,
+    /// Play from the last frame to the first. Streamed animations can only play forwards.
+    pub(crate) reverse: bool,
+    /// Index of the frame on screen. Streamed animations keep counting across loops.
+    pub(crate) current_frame: usize,
+    pub(crate) clear_color: wgpu::Color,
 }
 
 impl Scene for GifScene {
+    fn update(&mut self, _device: &Device, queue: &Queue, clock: &dyn Clock) {
+        let now = clock.now();
+        self.playback.start(now);
+        let played = self.playback.played(now);
+
+        match &mut self.frames {
+            GifFrames::Cached { frames, duration } => {
+                let position = played.as_nanos() % duration.as_nanos();
+                let index = frame_at(frames, Duration::from_nanos(position as u64), self.reverse);
+                if index != self.current_frame {
+                    self.image.texture().write(queue, frames[index].buffer());
+                    self.current_frame = index;
+                }
+            }
+            GifFrames::Streamed {
+                stream,
+                next_frame_at,
+            } => {
+                // Catch up by skipping frames when behind, only uploading the newest. In real
+                // time, a frame that isn't decoded yet holds the current one a little longer.
+                let mut latest = None;
+                while played >= *next_frame_at {
+                    let Ok(Some(frame)) = stream.next_frame(!clock.is_real_time()) else {
+                        break;
+                    };
+                    *next_frame_at += frame_delay(&frame);
+                    self.current_frame += 1;
+                    latest = Some(frame);
+                }
+                if let Some(frame) = latest {
+                    self.image.texture().write(queue, frame.buffer());
+                }
+            }
+        }
+
+        self.image.animate(queue, now);
+    }
+
     fn render_scene(
         &mut self,
         encoder: &mut CommandEncoder,
         view: &TextureView,
         pipeline: &RenderPipeline,
     ) {
-        let current_time = std::time::Instant::now();
-
-        if self.first_load {
-            self.first_load = false;
-            self.time_loaded = Some(std::time::Instant::now());
-            self.time_till_next_frame = self.frames.get(0).unwrap().1.as_millis();
-        }
-
-        if self.time_loaded.is_some() {
-            let time = self
-                .time_loaded
-                .unwrap()
-                .checked_add(Duration::from_millis(
-                    (self.time_till_next_frame).try_into().unwrap(),
-                ))
-                .unwrap();
-            if time < current_time {
-                self.current_frame += 1;
-                if self.current_frame > (self.frames.len() - 1) as u32 {
-                    self.current_frame = 0;
-                }
-
-                self.time_loaded = Some(current_time);
-                self.time_till_next_frame = self
-                    .frames
-                    .get(self.current_frame as usize)
-                    .unwrap()
-                    .1
-                    .as_millis();
-            }
-        }
-
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render Pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                 view,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: 0.0,
-                        g: 0.5,
-                        b: 0.3,
-                        a: 1.0,
-                    }),
+                    load: wgpu::LoadOp::Clear(self.clear_color),
                     store: true,
                 },
             })],
             depth_stencil_attachment: None,
         });
 
-        render_pass.set_pipeline(pipeline);
+        self.image.draw(&mut render_pass, pipeline);
+    }
 
-        let image = &self.frames.get(self.current_frame as usize).unwrap().0;
+    fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        self.image.resize(device, (config.width, config.height));
+    }
+}
 
-        render_pass.set_bind_group(0, image.bind_group.as_ref().unwrap(), &[]);
-        render_pass.set_vertex_buffer(0, image.vertex_buffer.as_ref().unwrap().slice(..));
-        render_pass.set_index_buffer(
-            image.index_buffer.as_ref().unwrap().slice(..),
-            wgpu::IndexFormat::Uint16,
-        );
+impl GifScene {
+    /// `image`'s texture has the animation's canvas size and already shows its first frame.
+    pub(crate) fn new(image: Image, frames: GifFrames, clear_color: wgpu::Color) -> Self {
+        GifScene {
+            image,
+            frames,
+            playback: Playback::new(1.0),
+            reverse: false,
+            current_frame: 0,
+            clear_color,
+        }
+    }
 
-        render_pass.draw_indexed(0..image.plane.get_indices().len() as u32, 0, 0..1);
+    /// Clock of the animation, for pausing it or changing its speed.
+    pub fn playback_mut(&mut self) -> &mut Playback {
+        &mut self.playback
+    }
+
+    pub fn is_reversed(&self) -> bool {
+        self.reverse
     }
-}
 
-impl GifScene {}
+    /// Changes direction at `now`, staying on the frame on screen. Streamed animations
+    /// ignore this and keep playing forwards.
+    pub fn set_reverse(&mut self, now: Duration, reverse: bool) {
+        if reverse == self.reverse {
+            return;
+        }
+        let GifFrames::Cached { duration, .. } = &self.frames else {
+            return;
+        };
+        let position = self.playback.played(now).as_nanos() % duration.as_nanos();
+        self.playback
+            .seek(now, *duration - Duration::from_nanos(position as u64));
+        self.reverse = reverse;
+    }
+}
@@ -1,14 +1,19 @@
-use wgpu::{CommandEncoder, RenderPipeline, TextureView};
+use wgpu::{CommandEncoder, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureView};
 
-use crate::renderer::primitives::image::Image;
+use crate::renderer::{clock::Clock, primitives::image::Image};
 
 use super::scene::Scene;
 
 pub struct ImageScene {
-    pub image: Image,
+    pub(crate) image: Image,
+    pub(crate) clear_color: wgpu::Color,
 }
 
 impl Scene for ImageScene {
+    fn update(&mut self, _device: &Device, queue: &Queue, clock: &dyn Clock) {
+        self.image.animate(queue, clock.now());
+    }
+
     fn render_scene(
         &mut self,
         encoder: &mut CommandEncoder,
@@ -21,29 +26,18 @@ impl Scene for ImageScene {
                 view,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: 0.0,
-                        g: 0.5,
-                        b: 0.3,
-                        a: 1.0,
-                    }),
+                    load: wgpu::LoadOp::Clear(self.clear_color),
                     store: true,
                 },
             })],
             depth_stencil_attachment: None,
         });
 
-        render_pass.set_pipeline(pipeline);
-
-        let bind_group = self.image.bind_group.as_ref().unwrap();
-        render_pass.set_bind_group(0, bind_group, &[]);
-        render_pass.set_vertex_buffer(0, self.image.vertex_buffer.as_ref().unwrap().slice(..));
-        render_pass.set_index_buffer(
-            self.image.index_buffer.as_ref().unwrap().slice(..),
-            wgpu::IndexFormat::Uint16,
-        );
+        self.image.draw(&mut render_pass, pipeline);
+    }
 
-        render_pass.draw_indexed(0..self.image.plane.get_indices().len() as u32, 0, 0..1);
+    fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        self.image.resize(device, (config.width, config.height));
     }
 }
 
@@ -1,4 +1,8 @@
 pub mod gif_scene;
 pub mod image_scene;
+pub mod parallax_scene;
 pub mod scene;
+pub mod schedule_scene;
+pub mod slideshow_scene;
 pub mod test_image_scene;
+pub mod video_scene;
@@ -0,0 +1,215 @@
+use std::{f32::consts::TAU, time::Duration};
+
+use cgmath::{Vector2, Zero};
+use wgpu::{CommandEncoder, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureView};
+
+use crate::{
+    manifest::ParallaxManifest,
+    renderer::{clock::Clock, primitives::image::Image},
+};
+
+use super::{scene::Scene, test_image_scene::TestImageScene};
+
+/// Image layers shifting away from the cursor by their depth, easing after it as it moves.
+/// Without a cursor, or once it has rested for `idle_after`, the layers drift along a
+/// figure eight instead.
+pub struct ParallaxScene {
+    pub(crate) manifest: ParallaxManifest,
+    /// The layers, drawn like a list of images.
+    pub(crate) layers: TestImageScene,
+    /// Cursor position the layers are shifted by, trailing the cursor or the drift.
+    pub(crate) position: Vector2<f32>,
+    /// Manifest offset of every layer, which the shift is added to.
+    offsets: Vec<Vector2<f32>>,
+    /// Last position passed to [`set_cursor`](Scene::set_cursor).
+    cursor: Option<Vector2<f32>>,
+    /// The cursor moved since the last update.
+    moved: bool,
+    /// When the cursor last moved. `None` until it does.
+    moved_at: Option<Duration>,
+    /// Time of the last update, `None` before the first.
+    updated_at: Option<Duration>,
+}
+
+impl ParallaxScene {
+    /// Layers from `images`, one for each of the manifest's layers, in the same order.
+    pub(crate) fn new(
+        images: Vec<Image>,
+        manifest: &ParallaxManifest,
+        clear_color: wgpu::Color,
+    ) -> Self {
+        ParallaxScene {
+            manifest: manifest.clone(),
+            offsets: images.iter().map(|i| i.transform().translation).collect(),
+            layers: TestImageScene {
+                images,
+                clear_color,
+            },
+            position: Vector2::zero(),
+            cursor: None,
+            moved: false,
+            moved_at: None,
+            updated_at: None,
+        }
+    }
+
+    /// Where the idle layers are `now`.
+    fn drift(&self, now: Duration) -> Vector2<f32> {
+        let angle = now.as_secs_f32() / self.manifest.drift_period * TAU;
+        Vector2::new(angle.sin(), (2.0 * angle).sin() * 0.5) * self.manifest.drift_radius
+    }
+
+    /// The cursor if it is in use at `now`, `None` when the layers drift.
+    fn active_cursor(&self, now: Duration) -> Option<Vector2<f32>> {
+        let idle_after = Duration::from_secs_f32(self.manifest.idle_after);
+        let moved_at = self.moved_at?;
+        (now.saturating_sub(moved_at) < idle_after)
+            .then_some(self.cursor)
+            .flatten()
+    }
+}
+
+impl Scene for ParallaxScene {
+    fn update(&mut self, _device: &Device, queue: &Queue, clock: &dyn Clock) {
+        let now = clock.now();
+        if std::mem::take(&mut self.moved) {
+            self.moved_at = Some(now);
+        }
+
+        let target = self.active_cursor(now).unwrap_or_else(|| self.drift(now));
+        self.position = match self.updated_at {
+            Some(updated_at) if self.manifest.smoothing > 0.0 => {
+                // Frame rate independent exponential easing
+                let elapsed = now.saturating_sub(updated_at).as_secs_f32();
+                let factor = 1.0 - (-elapsed / self.manifest.smoothing).exp();
+                self.position + (target - self.position) * factor
+            }
+            _ => target,
+        };
+        self.updated_at = Some(now);
+
+        for ((image, offset), layer) in self
+            .layers
+            .images
+            .iter_mut()
+            .zip(&self.offsets)
+            .zip(&self.manifest.layers)
+        {
+            // Animated offsets replace the manifest's, the shift applies on top of either
+            image.transform_mut().translation = *offset;
+            image.advance(now);
+            image.transform_mut().translation -= self.position * layer.depth;
+            image.write_uniform(queue);
+        }
+    }
+
+    fn render_scene(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        view: &TextureView,
+        pipeline: &RenderPipeline,
+    ) {
+        self.layers.render_scene(encoder, view, pipeline);
+    }
+
+    fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        self.layers.resize(device, config);
+    }
+
+    fn set_cursor(&mut self, position: Option<[f32; 2]>) {
+        let position = position.map(Vector2::from);
+        if position.is_some() && position != self.cursor {
+            self.moved = true;
+        }
+        self.cursor = position;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::InnerSpace;
+    use winit::dpi::PhysicalPosition;
+
+    use super::*;
+    use crate::{
+        manifest::Manifest,
+        renderer::{
+            engine::{tests::headless, Engine},
+            scenes::scene::SceneType,
+        },
+    };
+
+    fn translations(engine: &mut Engine) -> Vec<Vector2<f32>> {
+        match engine.scene_mut() {
+            Some(SceneType::Parallax(parallax)) => parallax
+                .layers
+                .images
+                .iter()
+                .map(|image| image.transform().translation)
+                .collect(),
+            _ => panic!("not a parallax scene"),
+        }
+    }
+
+    fn assert_near(actual: Vector2<f32>, expected: Vector2<f32>) {
+        assert!(
+            (actual - expected).magnitude() < 1e-3,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    #[test]
+    fn layers_shift_by_depth_ease_after_the_cursor_and_drift_when_idle() {
+        let Some((mut engine, clock)) = headless(100, 100) else {
+            return;
+        };
+        let image = concat!(env!("CARGO_MANIFEST_DIR"), "/images/1.png");
+        let manifest = Manifest::from_toml(&format!(
+            r#"
+            [scene.parallax]
+            smoothing = 1.0
+            idle_after = 5.0
+            drift_radius = 0.5
+            drift_period = 4.0
+
+            [[scene.parallax.layers]]
+            path = "{image}"
+
+            [[scene.parallax.layers]]
+            path = "{image}"
+            offset = [0.25, 0.0]
+            depth = 0.2
+            "#
+        ))
+        .unwrap();
+        engine.load_scene(&manifest).unwrap();
+        let step = |engine: &mut Engine, ms| {
+            clock.advance(Duration::from_millis(ms));
+            engine.update();
+            translations(engine)
+        };
+
+        // The cursor at the right edge, halfway up
+        engine.set_cursor_position(Some(PhysicalPosition::new(100.0, 25.0)));
+        let cursor = Vector2::new(1.0, 0.5);
+        let first = step(&mut engine, 0);
+        // The first update jumps straight to the cursor, shifting by depth around the offset
+        assert_near(first[0], Vector2::zero());
+        assert_near(first[1], Vector2::new(0.25, 0.0) - cursor * 0.2);
+
+        // A move is followed part of the way, 1 - e^-1 after one smoothing period
+        engine.set_cursor_position(Some(PhysicalPosition::new(0.0, 25.0)));
+        let moved = Vector2::new(-1.0, 0.5);
+        let eased = step(&mut engine, 1000);
+        let position = cursor + (moved - cursor) * (1.0 - (-1.0f32).exp());
+        assert_near(eased[0], Vector2::zero());
+        assert_near(eased[1], Vector2::new(0.25, 0.0) - position * 0.2);
+
+        // Long after the cursor rests, the layers have caught up with the drift
+        let drifted = step(&mut engine, 60_000);
+        let angle = 61.0 / 4.0 * TAU;
+        let drift = Vector2::new(angle.sin(), (2.0 * angle).sin() * 0.5) * 0.5;
+        assert_near(drifted[0], Vector2::zero());
+        assert_near(drifted[1], Vector2::new(0.25, 0.0) - drift * 0.2);
+    }
+}
@@ -1,24 +1,66 @@
-use wgpu::{CommandEncoder, RenderPipeline, TextureView};
+use wgpu::{CommandEncoder, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureView};
 
-use super::{gif_scene::GifScene, image_scene::ImageScene, test_image_scene::TestImageScene};
+use crate::renderer::{clock::Clock, engine::EngineEvent};
+
+use super::{
+    gif_scene::GifScene, image_scene::ImageScene, parallax_scene::ParallaxScene,
+    schedule_scene::ScheduleScene, slideshow_scene::SlideshowScene,
+    test_image_scene::TestImageScene, video_scene::VideoScene,
+};
 
 pub trait Scene {
+    /// Advances animations to the clock's time and uploads what changed, creating GPU
+    /// resources with `device` for scenes that load while running. Called once per frame
+    /// before rendering.
+    fn update(&mut self, device: &Device, queue: &Queue, clock: &dyn Clock);
+
     fn render_scene(
         &mut self,
         encoder: &mut CommandEncoder,
         view: &TextureView,
         pipeline: &RenderPipeline,
     );
+
+    /// Refits the scene to a new output size.
+    fn resize(&mut self, device: &Device, config: &SurfaceConfiguration);
+
+    /// Tells the scene where the cursor is, in clip space where the output spans -1..1 and y
+    /// points up, or `None` when it isn't over the output. Called before every update, so
+    /// scenes compare it with the last position to notice movement. Ignored by scenes not
+    /// following the cursor.
+    fn set_cursor(&mut self, _position: Option<[f32; 2]>) {}
+
+    /// Events the scene raised since the last call, passed on by
+    /// [`Engine::update`](crate::renderer::engine::Engine::update).
+    fn take_events(&mut self) -> Vec<EngineEvent> {
+        Vec::new()
+    }
 }
 
-#[allow(dead_code)]
+#[allow(clippy::large_enum_variant)]
 pub enum SceneType {
     Image(ImageScene),
     TestImages(TestImageScene),
     Gif(GifScene),
+    Video(VideoScene),
+    Slideshow(SlideshowScene),
+    Schedule(ScheduleScene),
+    Parallax(ParallaxScene),
 }
 
 impl Scene for SceneType {
+    fn update(&mut self, device: &Device, queue: &Queue, clock: &dyn Clock) {
+        match self {
+            SceneType::Image(image) => image.update(device, queue, clock),
+            SceneType::TestImages(test_images) => test_images.update(device, queue, clock),
+            SceneType::Gif(gif_images) => gif_images.update(device, queue, clock),
+            SceneType::Video(video) => video.update(device, queue, clock),
+            SceneType::Slideshow(slideshow) => slideshow.update(device, queue, clock),
+            SceneType::Schedule(schedule) => schedule.update(device, queue, clock),
+            SceneType::Parallax(parallax) => parallax.update(device, queue, clock),
+        }
+    }
+
     fn render_scene(
         &mut self,
         encoder: &mut CommandEncoder,
@@ -29,6 +71,39 @@ impl Scene for SceneType {
             SceneType::Image(image) => image.render_scene(encoder, view, pipeline),
             SceneType::TestImages(test_images) => test_images.render_scene(encoder, view, pipeline),
             SceneType::Gif(gif_images) => gif_images.render_scene(encoder, view, pipeline),
+            SceneType::Video(video) => video.render_scene(encoder, view, pipeline),
+            SceneType::Slideshow(slideshow) => slideshow.render_scene(encoder, view, pipeline),
+            SceneType::Schedule(schedule) => schedule.render_scene(encoder, view, pipeline),
+            SceneType::Parallax(parallax) => parallax.render_scene(encoder, view, pipeline),
+        }
+    }
+
+    fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        match self {
+            SceneType::Image(image) => image.resize(device, config),
+            SceneType::TestImages(test_images) => test_images.resize(device, config),
+            SceneType::Gif(gif_images) => gif_images.resize(device, config),
+            SceneType::Video(video) => video.resize(device, config),
+            SceneType::Slideshow(slideshow) => slideshow.resize(device, config),
+            SceneType::Schedule(schedule) => schedule.resize(device, config),
+            SceneType::Parallax(parallax) => parallax.resize(device, config),
+        }
+    }
+
+    fn set_cursor(&mut self, position: Option<[f32; 2]>) {
+        match self {
+            SceneType::Schedule(schedule) => schedule.set_cursor(position),
+            SceneType::Parallax(parallax) => parallax.set_cursor(position),
+            // Slides are never parallax scenes
+            _ => {}
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<EngineEvent> {
+        match self {
+            SceneType::Slideshow(slideshow) => slideshow.take_events(),
+            SceneType::Schedule(schedule) => schedule.take_events(),
+            _ => Vec::new(),
         }
     }
 }
@@ -0,0 +1,271 @@
+use std::sync::Arc;
+
+use wgpu::{CommandEncoder, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureView};
+
+use crate::{
+    manifest::ScheduleManifest,
+    renderer::{
+        clock::Clock,
+        engine::EngineEvent,
+        image_effects::ImagePipelines,
+        loader,
+        transitions::{Transition, TransitionPipelines},
+    },
+};
+
+use super::scene::{Scene, SceneType};
+
+/// Shows the scene of the schedule entry whose start most recently passed on the clock's
+/// local time, switching through the schedule's transition at every entry's start. Each
+/// entry's scene is loaded when it becomes active.
+pub struct ScheduleScene {
+    pub(crate) manifest: ScheduleManifest,
+    /// Index of the entry on screen.
+    pub(crate) active: Option<usize>,
+    /// `None` until the first update, which loads the active entry without a transition.
+    pub(crate) current: Option<Box<SceneType>>,
+    /// Transition from the previous entry's scene, which it holds, to `current`.
+    pub(crate) transition: Option<Box<Transition>>,
+    pub(crate) transition_quality: f32,
+    pub(crate) clear_color: wgpu::Color,
+    /// Where the schedule sits in the manifest, for errors loading entries.
+    key: String,
+    /// Output the scenes are fitted to, kept for loading the next ones.
+    config: SurfaceConfiguration,
+    pipelines: Arc<ImagePipelines>,
+    transition_pipelines: Arc<TransitionPipelines>,
+    /// An entry that failed to load, not retried until another one has become active.
+    failed: Option<usize>,
+}
+
+impl ScheduleScene {
+    pub(crate) fn new(
+        config: &SurfaceConfiguration,
+        pipelines: &Arc<ImagePipelines>,
+        transition_pipelines: &Arc<TransitionPipelines>,
+        manifest: &ScheduleManifest,
+        key: &str,
+        clear_color: wgpu::Color,
+        transition_quality: f32,
+    ) -> Self {
+        ScheduleScene {
+            manifest: manifest.clone(),
+            active: None,
+            current: None,
+            transition: None,
+            transition_quality,
+            clear_color,
+            key: key.to_string(),
+            config: config.clone(),
+            pipelines: pipelines.clone(),
+            transition_pipelines: transition_pipelines.clone(),
+            failed: None,
+        }
+    }
+
+    /// Loads entry `index` and switches to it, through the transition unless nothing is on
+    /// screen yet.
+    fn switch_to(&mut self, device: &Device, queue: &Queue, clock: &dyn Clock, index: usize) {
+        let key = format!("{}.entries[{index}].scene", self.key);
+        let scene = loader::load_scene_manifest(
+            device,
+            queue,
+            &self.config,
+            &self.pipelines,
+            &self.transition_pipelines,
+            &self.manifest.entries[index].scene,
+            &key,
+            self.clear_color,
+            self.transition_quality,
+        );
+        let scene = match scene {
+            Ok(scene) => scene,
+            Err(e) => {
+                log::warn!("{e:#}");
+                self.failed = Some(index);
+                return;
+            }
+        };
+        self.failed = None;
+        self.active = Some(index);
+
+        let Some(previous) = self.current.replace(Box::new(scene)) else {
+            return;
+        };
+        let Some(settings) = &self.manifest.transition else {
+            return;
+        };
+        self.transition = Transition::start_nested(
+            device,
+            &self.config,
+            &self.transition_pipelines,
+            *previous,
+            settings,
+            &format!("{}.transition", self.key),
+            self.transition_quality,
+            clock.now(),
+        );
+    }
+}
+
+impl Scene for ScheduleScene {
+    fn update(&mut self, device: &Device, queue: &Queue, clock: &dyn Clock) {
+        let now = clock.now();
+
+        if self.transition.as_ref().is_some_and(|t| t.is_finished(now)) {
+            self.transition = None;
+        }
+        // Without any entry starting around now, e.g. only sunrises during polar night, the
+        // scene on screen stays, or the first entry's if none is yet
+        let due = match self.manifest.active_entry(clock.local_time()) {
+            Some(index) => Some(index),
+            None if self.current.is_none() => Some(0),
+            None => None,
+        };
+        if let Some(index) = due {
+            let changed = self.current.is_none() || self.active != Some(index);
+            // A new transition waits for the running one to finish
+            if changed && self.failed != Some(index) && self.transition.is_none() {
+                self.switch_to(device, queue, clock, index);
+            }
+        }
+
+        if let Some(current) = &mut self.current {
+            current.update(device, queue, clock);
+        }
+        if let Some(transition) = &mut self.transition {
+            transition.scene_mut().update(device, queue, clock);
+            transition.update(queue, now);
+        }
+    }
+
+    fn render_scene(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        view: &TextureView,
+        pipeline: &RenderPipeline,
+    ) {
+        match (&mut self.transition, &mut self.current) {
+            (Some(transition), current) => {
+                transition.transition(encoder, view, pipeline, current.as_deref_mut())
+            }
+            (None, Some(current)) => current.render_scene(encoder, view, pipeline),
+            (None, None) => {
+                // Nothing loaded yet
+                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Render Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(self.clear_color),
+                            store: true,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                });
+            }
+        }
+    }
+
+    fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        self.config = config.clone();
+        if let Some(current) = &mut self.current {
+            current.resize(device, config);
+        }
+        if let Some(transition) = &mut self.transition {
+            transition.resize(device, config);
+            transition.scene_mut().resize(device, config);
+        }
+    }
+
+    fn set_cursor(&mut self, position: Option<[f32; 2]>) {
+        if let Some(current) = &mut self.current {
+            current.set_cursor(position);
+        }
+        if let Some(transition) = &mut self.transition {
+            transition.scene_mut().set_cursor(position);
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<EngineEvent> {
+        let mut events = Vec::new();
+        if let Some(current) = &mut self.current {
+            events.append(&mut current.take_events());
+        }
+        if let Some(transition) = &mut self.transition {
+            events.append(&mut transition.scene_mut().take_events());
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{FixedOffset, TimeZone};
+
+    use super::*;
+    use crate::{
+        manifest::Manifest,
+        renderer::{
+            clock::ManualClock,
+            engine::{tests::headless, Engine},
+        },
+    };
+
+    fn schedule(engine: &mut Engine) -> &mut ScheduleScene {
+        match engine.scene_mut() {
+            Some(SceneType::Schedule(schedule)) => schedule,
+            _ => panic!("not a schedule"),
+        }
+    }
+
+    #[test]
+    fn switches_entries_through_the_transition() {
+        let Some((mut engine, _)) = headless(32, 32) else {
+            return;
+        };
+        let image = concat!(env!("CARGO_MANIFEST_DIR"), "/images/1.png");
+        let manifest = Manifest::from_toml(&format!(
+            r#"
+            [scene.schedule]
+            transition = {{ name = "fade", duration = 2.0 }}
+
+            [[scene.schedule.entries]]
+            at = "08:00"
+            scene.image.path = "{image}"
+
+            [[scene.schedule.entries]]
+            at = "20:00"
+            scene.image.path = "{image}"
+            "#
+        ))
+        .unwrap();
+        let offset = FixedOffset::east_opt(3600).unwrap();
+        let clock =
+            ManualClock::starting_at(offset.with_ymd_and_hms(2023, 1, 1, 7, 59, 59).unwrap());
+        engine.set_clock(clock.clone());
+        engine.set_transition_quality(0.5);
+        engine.load_scene(&manifest).unwrap();
+
+        // The entry from the evening before shows at once
+        engine.update();
+        let scene = schedule(&mut engine);
+        assert_eq!(scene.active, Some(1));
+        assert!(scene.transition.is_none());
+        assert_eq!(scene.transition_quality, 0.5);
+
+        clock.set(Duration::from_secs(1));
+        engine.update();
+        let scene = schedule(&mut engine);
+        assert_eq!(scene.active, Some(0));
+        assert!(scene.transition.is_some());
+
+        clock.set(Duration::from_secs(4));
+        engine.update();
+        assert!(schedule(&mut engine).transition.is_none());
+        engine.render().unwrap();
+    }
+}
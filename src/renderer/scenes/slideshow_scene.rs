@@ -0,0 +1,263 @@
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    time::Duration,
+};
+
+use rand::seq::SliceRandom;
+use wgpu::{CommandEncoder, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureView};
+
+use crate::{
+    manifest::{SlideOrder, SlideshowManifest},
+    renderer::{
+        clock::Clock,
+        engine::EngineEvent,
+        image_effects::ImagePipelines,
+        loader::{self, DecodedSlide},
+        transitions::{Transition, TransitionPipelines},
+    },
+};
+
+use super::scene::{Scene, SceneType};
+
+/// Shows images and animations one after the other, each for `interval`, switching through
+/// the manifest's transition. The next slide is decoded on a background thread while the
+/// current one is on screen.
+pub struct SlideshowScene {
+    pub(crate) manifest: SlideshowManifest,
+    pub(crate) slides: Vec<PathBuf>,
+    /// Slides left in this pass through `slides`, the next one first.
+    pub(crate) upcoming: VecDeque<PathBuf>,
+    pub(crate) interval: Duration,
+    /// Path of the slide on screen.
+    pub(crate) current_path: PathBuf,
+    pub(crate) current: Box<SceneType>,
+    /// Transition from the previous slide, which it holds, to `current`.
+    pub(crate) transition: Option<Box<Transition>>,
+    pub(crate) transition_quality: f32,
+    pub(crate) clear_color: wgpu::Color,
+    /// Where the slideshow sits in the manifest, for warnings about its transition.
+    key: String,
+    /// Output the slides are fitted to, kept for loading the next ones.
+    config: SurfaceConfiguration,
+    pipelines: Arc<ImagePipelines>,
+    transition_pipelines: Arc<TransitionPipelines>,
+    /// When the slide on screen appeared. `None` before the first update.
+    shown_at: Option<Duration>,
+    /// The next slide, decoding in the background.
+    next: Option<Receiver<(PathBuf, anyhow::Result<DecodedSlide>)>>,
+    /// Slides skipped since the events were last taken.
+    events: Vec<EngineEvent>,
+}
+
+impl SlideshowScene {
+    /// Loads the first slide right away and starts decoding the second.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        device: &Device,
+        queue: &Queue,
+        config: &SurfaceConfiguration,
+        pipelines: &Arc<ImagePipelines>,
+        transition_pipelines: &Arc<TransitionPipelines>,
+        manifest: &SlideshowManifest,
+        key: &str,
+        clear_color: wgpu::Color,
+        transition_quality: f32,
+    ) -> anyhow::Result<Self> {
+        let slides = manifest.slides()?;
+        anyhow::ensure!(!slides.is_empty(), "no slides to show");
+
+        let mut upcoming = VecDeque::from(slides.clone());
+        if manifest.order == SlideOrder::Shuffle {
+            upcoming.make_contiguous().shuffle(&mut rand::thread_rng());
+        }
+        let current_path = upcoming.pop_front().unwrap();
+        let decoded = loader::decode_slide(&current_path)?;
+        let current = loader::upload_slide(
+            device,
+            queue,
+            config,
+            pipelines,
+            manifest.slide(current_path.clone()),
+            decoded,
+            clear_color,
+        )?;
+
+        let mut scene = SlideshowScene {
+            manifest: manifest.clone(),
+            slides,
+            upcoming,
+            interval: Duration::from_secs_f32(manifest.interval),
+            current_path,
+            current: Box::new(current),
+            transition: None,
+            transition_quality,
+            clear_color,
+            key: key.to_string(),
+            config: config.clone(),
+            pipelines: pipelines.clone(),
+            transition_pipelines: transition_pipelines.clone(),
+            shown_at: None,
+            next: None,
+            events: Vec::new(),
+        };
+        scene.preload();
+        Ok(scene)
+    }
+
+    /// Starts decoding the slide after the current one. Slideshows of a single slide never
+    /// change.
+    fn preload(&mut self) {
+        if self.slides.len() < 2 {
+            return;
+        }
+        if self.upcoming.is_empty() {
+            let mut pass = self.slides.clone();
+            if self.manifest.order == SlideOrder::Shuffle {
+                pass.shuffle(&mut rand::thread_rng());
+                // Don't show the same slide twice in a row across passes
+                if pass[0] == self.current_path {
+                    let last = pass.len() - 1;
+                    pass.swap(0, last);
+                }
+            }
+            self.upcoming = pass.into();
+        }
+        let path = self.upcoming.pop_front().unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        self.next = Some(receiver);
+        let spawned = std::thread::Builder::new()
+            .name("slide decoder".to_string())
+            .spawn(move || {
+                let decoded = loader::decode_slide(&path);
+                let _ = sender.send((path, decoded));
+            });
+        if let Err(e) = spawned {
+            log::warn!("failed to start decoding the next slide: {e}");
+            self.next = None;
+        }
+    }
+
+    /// Swaps in the preloaded slide if it is ready, or waits for it under a clock that
+    /// isn't real time. Slides that fail to load are skipped.
+    fn advance(&mut self, device: &Device, queue: &Queue, clock: &dyn Clock) {
+        let Some(next) = &self.next else {
+            return;
+        };
+        let received = if clock.is_real_time() {
+            match next.try_recv() {
+                Ok(received) => received,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.preload();
+                    return;
+                }
+            }
+        } else {
+            match next.recv() {
+                Ok(received) => received,
+                Err(_) => {
+                    self.preload();
+                    return;
+                }
+            }
+        };
+        self.preload();
+
+        let (path, decoded) = received;
+        let slide = decoded.and_then(|decoded| {
+            loader::upload_slide(
+                device,
+                queue,
+                &self.config,
+                &self.pipelines,
+                self.manifest.slide(path.clone()),
+                decoded,
+                self.clear_color,
+            )
+        });
+        let slide = match slide {
+            Ok(slide) => slide,
+            Err(e) => {
+                log::warn!("skipping slide `{}`: {e:#}", path.display());
+                self.events.push(EngineEvent::SlideSkipped {
+                    path,
+                    error: format!("{e:#}"),
+                });
+                return;
+            }
+        };
+
+        let now = clock.now();
+        let previous = std::mem::replace(self.current.as_mut(), slide);
+        self.current_path = path;
+        self.shown_at = Some(now);
+
+        let Some(settings) = &self.manifest.transition else {
+            return;
+        };
+        self.transition = Transition::start_nested(
+            device,
+            &self.config,
+            &self.transition_pipelines,
+            previous,
+            settings,
+            &format!("{}.transition", self.key),
+            self.transition_quality,
+            now,
+        );
+    }
+}
+
+impl Scene for SlideshowScene {
+    fn update(&mut self, device: &Device, queue: &Queue, clock: &dyn Clock) {
+        let now = clock.now();
+        let shown_at = *self.shown_at.get_or_insert(now);
+
+        if self.transition.as_ref().is_some_and(|t| t.is_finished(now)) {
+            self.transition = None;
+        }
+        // The interval counts from when a slide appears, so transitions eat into it
+        if self.transition.is_none() && now >= shown_at + self.interval {
+            self.advance(device, queue, clock);
+        }
+
+        self.current.update(device, queue, clock);
+        if let Some(transition) = &mut self.transition {
+            transition.scene_mut().update(device, queue, clock);
+            transition.update(queue, now);
+        }
+    }
+
+    fn render_scene(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        view: &TextureView,
+        pipeline: &RenderPipeline,
+    ) {
+        match &mut self.transition {
+            Some(transition) => {
+                transition.transition(encoder, view, pipeline, Some(self.current.as_mut()))
+            }
+            None => self.current.render_scene(encoder, view, pipeline),
+        }
+    }
+
+    fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        self.config = config.clone();
+        self.current.resize(device, config);
+        if let Some(transition) = &mut self.transition {
+            transition.resize(device, config);
+            transition.scene_mut().resize(device, config);
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<EngineEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
@@ -1,14 +1,21 @@
-use wgpu::{CommandEncoder, RenderPipeline, TextureView};
+use wgpu::{CommandEncoder, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureView};
 
-use crate::renderer::primitives::image::Image;
+use crate::renderer::{clock::Clock, primitives::image::Image};
 
 use super::scene::Scene;
 
 pub struct TestImageScene {
-    pub images: Vec<Image>,
+    pub(crate) images: Vec<Image>,
+    pub(crate) clear_color: wgpu::Color,
 }
 
 impl Scene for TestImageScene {
+    fn update(&mut self, _device: &Device, queue: &Queue, clock: &dyn Clock) {
+        for image in &mut self.images {
+            image.animate(queue, clock.now());
+        }
+    }
+
     fn render_scene(
         &mut self,
         encoder: &mut CommandEncoder,
@@ -21,30 +28,25 @@ impl Scene for TestImageScene {
                 view,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: 0.0,
-                        g: 0.5,
-                        b: 0.3,
-                        a: 1.0,
-                    }),
+                    load: wgpu::LoadOp::Clear(self.clear_color),
                     store: true,
                 },
             })],
             depth_stencil_attachment: None,
         });
 
-        render_pass.set_pipeline(pipeline);
+        // Back to front, keeping manifest order for equal z-indices
+        let mut images: Vec<&Image> = self.images.iter().collect();
+        images.sort_by_key(|i| i.transform().z_index);
 
-        for i in self.images.iter() {
-            let bind_group = i.bind_group.as_ref().unwrap();
-            render_pass.set_bind_group(0, bind_group, &[]);
-            render_pass.set_vertex_buffer(0, i.vertex_buffer.as_ref().unwrap().slice(..));
-            render_pass.set_index_buffer(
-                i.index_buffer.as_ref().unwrap().slice(..),
-                wgpu::IndexFormat::Uint16,
-            );
+        for image in images {
+            image.draw(&mut render_pass, pipeline);
+        }
+    }
 
-            render_pass.draw_indexed(0..i.plane.get_indices().len() as u32, 0, 0..1);
+    fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        for image in &mut self.images {
+            image.resize(device, (config.width, config.height));
         }
     }
 }
@@ -0,0 +1,236 @@
+use std::time::Duration;
+
+use wgpu::{CommandEncoder, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureView};
+
+use crate::renderer::{clock::Clock, primitives::image::Image, video::VideoDecoder};
+
+use super::scene::Scene;
+
+/// Plays a video by streaming frames decoded by an `ffmpeg` subprocess into a single texture.
+pub struct VideoScene {
+    pub(crate) image: Image,
+    pub(crate) decoder: VideoDecoder,
+    pub(crate) clear_color: wgpu::Color,
+    pub(crate) looping: bool,
+    pub(crate) speed: f32,
+    /// Where in the video the decoder started.
+    pub(crate) start: Duration,
+    /// Frames taken from the decoder so far.
+    pub(crate) frames_shown: u64,
+    /// When playback at the current speed began, and how many frames had been shown by then.
+    /// Set on the first update.
+    pub(crate) clock: Option<(Duration, u64)>,
+    /// The decoder ran out of frames. The last one stays on screen.
+    pub(crate) ended: bool,
+}
+
+impl Scene for VideoScene {
+    fn update(&mut self, _device: &Device, queue: &Queue, clock: &dyn Clock) {
+        let now = clock.now();
+        let (started, frames_before) = *self.clock.get_or_insert((now, self.frames_shown));
+        let elapsed = now.saturating_sub(started);
+        let played = elapsed.as_secs_f32() * self.speed * self.decoder.info.frame_rate;
+        // The first frame shows right away
+        let target = frames_before + played as u64 + 1;
+
+        // Skip frames when behind, keeping only the newest
+        let mut latest = None;
+        while !self.ended && self.frames_shown < target {
+            match self.decoder.next_frame(!clock.is_real_time()) {
+                Ok(Some(frame)) => {
+                    latest = Some(frame);
+                    self.frames_shown += 1;
+                }
+                Ok(None) => break,
+                Err(_) => self.ended = true,
+            }
+        }
+        if let Some(frame) = latest {
+            self.image.texture().write(queue, &frame);
+        }
+
+        self.image.animate(queue, now);
+    }
+
+    fn render_scene(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        view: &TextureView,
+        pipeline: &RenderPipeline,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(self.clear_color),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        self.image.draw(&mut render_pass, pipeline);
+    }
+
+    fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        self.image.resize(device, (config.width, config.height));
+    }
+}
+
+impl VideoScene {
+    pub(crate) fn new(
+        image: Image,
+        decoder: VideoDecoder,
+        clear_color: wgpu::Color,
+        looping: bool,
+        speed: f32,
+        start: Duration,
+    ) -> Self {
+        VideoScene {
+            image,
+            decoder,
+            clear_color,
+            looping,
+            speed,
+            start,
+            frames_shown: 0,
+            clock: None,
+            ended: false,
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Whether a video that doesn't loop is past its last frame, which stays on screen.
+    pub fn has_ended(&self) -> bool {
+        self.ended
+    }
+
+    /// Position of the frame on screen. Keeps counting past the end of looping videos.
+    pub fn position(&self) -> Duration {
+        let frames = self.frames_shown.saturating_sub(1) as f32;
+        self.start + Duration::from_secs_f32(frames / self.decoder.info.frame_rate)
+    }
+
+    /// Restarts decoding at `position`.
+    pub fn seek(&mut self, position: Duration) -> anyhow::Result<()> {
+        self.decoder = VideoDecoder::spawn(
+            &self.decoder.path,
+            self.decoder.info,
+            position,
+            self.looping,
+        )?;
+        self.start = position;
+        self.frames_shown = 0;
+        self.clock = None;
+        self.ended = false;
+        Ok(())
+    }
+
+    /// Changes the playback rate from the current frame on, `now` being a [`Clock`] reading.
+    ///
+    /// [`Clock`]: crate::renderer::clock::Clock
+    pub fn set_speed(&mut self, now: Duration, speed: f32) {
+        self.speed = speed.max(0.0);
+        // Anchored like the first update, which counts the frame on screen as played
+        if self.clock.is_some() {
+            self.clock = Some((now, self.frames_shown.saturating_sub(1)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        manifest::Manifest,
+        renderer::{
+            clock::ManualClock,
+            engine::{tests::headless, Engine},
+            scenes::scene::SceneType,
+            video,
+        },
+    };
+
+    /// A headless engine playing `path` on a manual clock.
+    fn play(path: &std::path::Path, looping: bool) -> Option<(Engine, ManualClock)> {
+        let (mut engine, clock) = headless(32, 24)?;
+        let manifest = Manifest::from_toml(&format!(
+            "[scene.video]\npath = {:?}\nloop = {looping}",
+            path.display().to_string()
+        ))
+        .unwrap();
+        engine.load_scene(&manifest).unwrap();
+        Some((engine, clock))
+    }
+
+    fn video(engine: &mut Engine) -> &mut VideoScene {
+        match engine.scene_mut() {
+            Some(SceneType::Video(video)) => video,
+            _ => panic!("not a video"),
+        }
+    }
+
+    fn frames_at(engine: &mut Engine, clock: &ManualClock, millis: u64) -> u64 {
+        clock.set(Duration::from_millis(millis));
+        engine.update();
+        video(engine).frames_shown
+    }
+
+    #[test]
+    fn follows_the_clock_and_holds_the_last_frame() {
+        let Some(path) = video::tests::clip("scene-end", 10) else {
+            return;
+        };
+        let Some((mut engine, clock)) = play(&path, false) else {
+            return;
+        };
+        assert_eq!(frames_at(&mut engine, &clock, 0), 1);
+        assert_eq!(frames_at(&mut engine, &clock, 550), 6);
+        assert!(!video(&mut engine).ended);
+
+        assert_eq!(frames_at(&mut engine, &clock, 5000), 10);
+        assert!(video(&mut engine).ended);
+        assert_eq!(frames_at(&mut engine, &clock, 6000), 10);
+        engine.render().unwrap();
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn looping_keeps_counting() {
+        let Some(path) = video::tests::clip("scene-loop", 10) else {
+            return;
+        };
+        let Some((mut engine, clock)) = play(&path, true) else {
+            return;
+        };
+        assert_eq!(frames_at(&mut engine, &clock, 0), 1);
+        assert_eq!(frames_at(&mut engine, &clock, 2500), 26);
+        assert!(!video(&mut engine).ended);
+        assert_eq!(video(&mut engine).position(), Duration::from_millis(2500));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn speed_changes_keep_the_frame_on_screen() {
+        let Some(path) = video::tests::clip("scene-speed", 10) else {
+            return;
+        };
+        let Some((mut engine, clock)) = play(&path, false) else {
+            return;
+        };
+        assert_eq!(frames_at(&mut engine, &clock, 0), 1);
+        assert_eq!(frames_at(&mut engine, &clock, 300), 4);
+
+        video(&mut engine).set_speed(Duration::from_millis(300), 2.0);
+        assert_eq!(frames_at(&mut engine, &clock, 300), 4);
+        assert_eq!(frames_at(&mut engine, &clock, 349), 4);
+        assert_eq!(frames_at(&mut engine, &clock, 360), 5);
+        assert_eq!(frames_at(&mut engine, &clock, 520), 8);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,282 @@
+use std::f64::consts::PI;
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde::Deserialize;
+
+/// When a schedule entry starts: a fixed local time, or sunrise or sunset at the schedule's
+/// location. Written `"07:30"`, `"07:30:15"`, `"sunrise"` or `"sunset"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum TimeOfDay {
+    At(NaiveTime),
+    Sunrise,
+    Sunset,
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "sunrise" => Ok(TimeOfDay::Sunrise),
+            "sunset" => Ok(TimeOfDay::Sunset),
+            time => NaiveTime::parse_from_str(time, "%H:%M")
+                .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M:%S"))
+                .map(TimeOfDay::At)
+                .map_err(|_| {
+                    format!("expected `HH:MM`, `HH:MM:SS`, `sunrise` or `sunset`, got `{time}`")
+                }),
+        }
+    }
+}
+
+impl TimeOfDay {
+    pub fn is_solar(&self) -> bool {
+        matches!(self, TimeOfDay::Sunrise | TimeOfDay::Sunset)
+    }
+
+    /// When this time of day falls on `date`, in the time zone `offset`. `location` is
+    /// `(latitude, longitude)` in degrees, needed for sunrise and sunset. `None` when the sun
+    /// doesn't rise or set that day, or without a location.
+    pub fn on(
+        &self,
+        date: NaiveDate,
+        offset: FixedOffset,
+        location: Option<(f64, f64)>,
+    ) -> Option<DateTime<FixedOffset>> {
+        match self {
+            TimeOfDay::At(time) => offset.from_local_datetime(&date.and_time(*time)).single(),
+            TimeOfDay::Sunrise | TimeOfDay::Sunset => {
+                let (latitude, longitude) = location?;
+                let (sunrise, sunset) = sun_times(date, latitude, longitude)?;
+                let time = if *self == TimeOfDay::Sunrise {
+                    sunrise
+                } else {
+                    sunset
+                };
+                Some(time.with_timezone(&offset))
+            }
+        }
+    }
+}
+
+/// Sunrise and sunset on `date` at a location in degrees, longitude east of Greenwich
+/// positive, following the sunrise equation. Accurate to a minute or two. `None` during polar
+/// day and night.
+pub fn sun_times(
+    date: NaiveDate,
+    latitude: f64,
+    longitude: f64,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    const J2000: f64 = 2451545.0;
+    const UNIX_EPOCH_JULIAN: f64 = 2440587.5;
+    let (sin, cos) = (
+        |deg: f64| deg.to_radians().sin(),
+        |deg: f64| deg.to_radians().cos(),
+    );
+
+    let unix_epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+    let days_since_epoch = date.signed_duration_since(unix_epoch).num_days();
+    // Julian day of the date, counted from J2000
+    let day = (days_since_epoch as f64 + UNIX_EPOCH_JULIAN - J2000 + 0.0008).ceil();
+    let mean_solar_noon = day - longitude / 360.0;
+
+    let mean_anomaly = (357.5291 + 0.98560028 * mean_solar_noon).rem_euclid(360.0);
+    let center = 1.9148 * sin(mean_anomaly)
+        + 0.02 * sin(2.0 * mean_anomaly)
+        + 0.0003 * sin(3.0 * mean_anomaly);
+    let ecliptic_longitude = (mean_anomaly + center + 180.0 + 102.9372).rem_euclid(360.0);
+    let transit = J2000 + mean_solar_noon + 0.0053 * sin(mean_anomaly)
+        - 0.0069 * sin(2.0 * ecliptic_longitude);
+
+    let declination = (sin(ecliptic_longitude) * sin(23.4397)).asin();
+    // -0.833° accounts for refraction and the sun's radius
+    let cos_hour_angle =
+        (sin(-0.833) - sin(latitude) * declination.sin()) / (cos(latitude) * declination.cos());
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None;
+    }
+    let hour_angle = cos_hour_angle.acos() / (2.0 * PI);
+
+    let to_utc = |julian: f64| {
+        let seconds = (julian - UNIX_EPOCH_JULIAN) * 86400.0;
+        Utc.timestamp_opt(seconds.round() as i64, 0).single()
+    };
+    Some((to_utc(transit - hour_angle)?, to_utc(transit + hour_angle)?))
+}
+
+/// Index of the entry whose start most recently passed at `now`, wrapping around to the
+/// previous day before the first one. Entries are `(start, offset)`; `None` if no entry can
+/// start around `now`, e.g. only sunrises during polar night.
+pub fn active_entry(
+    entries: impl IntoIterator<Item = (TimeOfDay, Duration)>,
+    now: DateTime<FixedOffset>,
+    location: Option<(f64, f64)>,
+) -> Option<usize> {
+    let today = now.date_naive();
+    let days = [today.pred_opt()?, today, today.succ_opt()?];
+    let offset = *now.offset();
+
+    entries
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, (start, shift))| {
+            days.iter()
+                .filter_map(move |date| start.on(*date, offset, location))
+                .map(move |time| (time + shift, i))
+        })
+        .filter(|(time, _)| *time <= now)
+        .max_by_key(|(time, _)| *time)
+        .map(|(_, i)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Timelike;
+
+    use super::*;
+    use crate::{
+        manifest::Manifest,
+        renderer::clock::{Clock, ManualClock},
+    };
+
+    const BERLIN: (f64, f64) = (52.52, 13.405);
+    const LONGYEARBYEN: (f64, f64) = (78.22, 15.65);
+
+    fn local(
+        date: (i32, u32, u32),
+        time: (u32, u32, u32),
+        hours_east: i32,
+    ) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(hours_east * 3600)
+            .unwrap()
+            .with_ymd_and_hms(date.0, date.1, date.2, time.0, time.1, time.2)
+            .unwrap()
+    }
+
+    fn at(time: &str) -> TimeOfDay {
+        TimeOfDay::try_from(time.to_string()).unwrap()
+    }
+
+    fn active(
+        entries: &[(&str, i64)],
+        now: DateTime<FixedOffset>,
+        location: Option<(f64, f64)>,
+    ) -> Option<usize> {
+        let entries = entries
+            .iter()
+            .map(|(time, minutes)| (at(time), Duration::minutes(*minutes)));
+        active_entry(entries, now, location)
+    }
+
+    #[test]
+    fn fixed_times_start_on_the_second() {
+        let entries = [("08:00", 0), ("20:00", 0)];
+        let day = (2023, 6, 21);
+        assert_eq!(active(&entries, local(day, (7, 59, 59), 2), None), Some(1));
+        assert_eq!(active(&entries, local(day, (8, 0, 0), 2), None), Some(0));
+        assert_eq!(active(&entries, local(day, (19, 59, 59), 2), None), Some(0));
+        assert_eq!(active(&entries, local(day, (20, 0, 0), 2), None), Some(1));
+    }
+
+    #[test]
+    fn last_entry_carries_past_midnight() {
+        let entries = [("06:00", 0), ("22:30", 0)];
+        assert_eq!(
+            active(&entries, local((2023, 6, 21), (23, 59, 59), 2), None),
+            Some(1)
+        );
+        assert_eq!(
+            active(&entries, local((2023, 6, 22), (0, 0, 0), 2), None),
+            Some(1)
+        );
+        assert_eq!(
+            active(&entries, local((2023, 6, 22), (5, 59, 0), 2), None),
+            Some(1)
+        );
+        // The same holds across the end of a year
+        assert_eq!(
+            active(&entries, local((2024, 1, 1), (0, 15, 0), 2), None),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn sun_times_match_published_times() {
+        let date = NaiveDate::from_ymd_opt(2023, 6, 21).unwrap();
+        let (sunrise, sunset) = sun_times(date, BERLIN.0, BERLIN.1).unwrap();
+        // 04:43 and 21:33 CEST
+        let expected_sunrise = Utc.with_ymd_and_hms(2023, 6, 21, 2, 43, 0).unwrap();
+        let expected_sunset = Utc.with_ymd_and_hms(2023, 6, 21, 19, 33, 0).unwrap();
+        assert!((sunrise - expected_sunrise).num_minutes().abs() <= 2);
+        assert!((sunset - expected_sunset).num_minutes().abs() <= 2);
+    }
+
+    #[test]
+    fn solar_entries_follow_the_sun_with_offsets() {
+        let date = NaiveDate::from_ymd_opt(2023, 6, 21).unwrap();
+        let (sunrise, sunset) = sun_times(date, BERLIN.0, BERLIN.1).unwrap();
+        let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+        let sunrise = sunrise.with_timezone(&offset);
+        let evening = sunset.with_timezone(&offset) - Duration::minutes(30);
+
+        let entries = [("sunrise", 0), ("12:00", 0), ("sunset", -30)];
+        let second = Duration::seconds(1);
+        assert_eq!(active(&entries, sunrise - second, Some(BERLIN)), Some(2));
+        assert_eq!(active(&entries, sunrise, Some(BERLIN)), Some(0));
+        assert_eq!(active(&entries, evening - second, Some(BERLIN)), Some(1));
+        assert_eq!(active(&entries, evening, Some(BERLIN)), Some(2));
+    }
+
+    #[test]
+    fn polar_night_has_no_sunrise() {
+        let date = NaiveDate::from_ymd_opt(2023, 12, 21).unwrap();
+        assert_eq!(sun_times(date, LONGYEARBYEN.0, LONGYEARBYEN.1), None);
+        let date = NaiveDate::from_ymd_opt(2023, 6, 21).unwrap();
+        assert_eq!(sun_times(date, LONGYEARBYEN.0, LONGYEARBYEN.1), None);
+
+        let now = local((2023, 12, 21), (12, 0, 0), 1);
+        assert_eq!(
+            active(&[("sunrise", 0), ("sunset", 0)], now, Some(LONGYEARBYEN)),
+            None
+        );
+        // Fixed times still apply
+        assert_eq!(
+            active(&[("sunrise", 0), ("09:00", 0)], now, Some(LONGYEARBYEN)),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn manifest_entries_follow_a_manual_clock() {
+        let manifest = Manifest::from_toml(
+            r#"
+            [scene.schedule]
+            latitude = 52.52
+            longitude = 13.405
+
+            [[scene.schedule.entries]]
+            at = "07:30"
+            scene.image.path = "day.png"
+
+            [[scene.schedule.entries]]
+            at = "sunset"
+            offset = -30
+            scene.image.path = "evening.png"
+            "#,
+        )
+        .unwrap();
+        let crate::manifest::SceneManifest::Schedule(schedule) = manifest.scene else {
+            panic!("not a schedule");
+        };
+
+        let clock = ManualClock::starting_at(local((2023, 6, 21), (7, 29, 0), 2));
+        assert_eq!(schedule.active_entry(clock.local_time()), Some(1));
+        clock.set(std::time::Duration::from_secs(60));
+        assert_eq!(clock.local_time().minute(), 30);
+        assert_eq!(schedule.active_entry(clock.local_time()), Some(0));
+        // 21:14, after half an hour before the 21:33 sunset
+        clock.set(std::time::Duration::from_secs(13 * 3600 + 45 * 60));
+        assert_eq!(schedule.active_entry(clock.local_time()), Some(1));
+    }
+}
@@ -0,0 +1,42 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+struct Constants {
+    // Output size in pixels
+    resolution : vec2<f32>,
+    // Seconds on the engine clock
+    time : f32,
+    // Effect parameters, see `EffectKind::passes`
+    params : vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> constants : Constants;
+
+// Output of the scene or of the previous effect
+@group(0) @binding(0)
+var t_input: texture_2d<f32>;
+@group(0) @binding(1)
+var s_input: sampler;
+
+// One direction of a separable gaussian blur: params.x is the radius in pixels, params.yz
+// the direction.
+@fragment
+fn main_fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+    let radius = constants.params.x;
+    let step = constants.params.yz / constants.resolution;
+    let sigma = max(radius / 2.0, 0.001);
+    let taps = i32(ceil(min(radius, 64.0)));
+
+    var sum = vec4<f32>(0.0);
+    var weights = 0.0;
+    for (var i = -taps; i <= taps; i++) {
+        let offset = f32(i);
+        let weight = exp(-(offset * offset) / (2.0 * sigma * sigma));
+        sum += textureSampleLevel(t_input, s_input, input.tex_coords + step * offset, 0.0) * weight;
+        weights += weight;
+    }
+    return sum / weights;
+}
@@ -0,0 +1,32 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+struct Constants {
+    // Output size in pixels
+    resolution : vec2<f32>,
+    // Seconds on the engine clock
+    time : f32,
+    // Effect parameters, see `EffectKind::passes`
+    params : vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> constants : Constants;
+
+// Output of the scene or of the previous effect
+@group(0) @binding(0)
+var t_input: texture_2d<f32>;
+@group(0) @binding(1)
+var s_input: sampler;
+
+// Splits red and blue apart radially: params.x is their offset in pixels at the edges.
+@fragment
+fn main_fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+    let shift = (input.tex_coords - 0.5) * 2.0 * constants.params.x / constants.resolution;
+    let color = textureSample(t_input, s_input, input.tex_coords);
+    let red = textureSample(t_input, s_input, input.tex_coords + shift).r;
+    let blue = textureSample(t_input, s_input, input.tex_coords - shift).b;
+    return vec4<f32>(red, color.g, blue, color.a);
+}
@@ -0,0 +1,34 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+struct Constants {
+    // Output size in pixels
+    resolution : vec2<f32>,
+    // Seconds on the engine clock
+    time : f32,
+    // Effect parameters, see `EffectKind::passes`
+    params : vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> constants : Constants;
+
+// Output of the scene or of the previous effect
+@group(0) @binding(0)
+var t_input: texture_2d<f32>;
+@group(0) @binding(1)
+var s_input: sampler;
+
+// params holds brightness, contrast, saturation and gamma, applied in that order.
+@fragment
+fn main_fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_input, s_input, input.tex_coords);
+    var rgb = color.rgb + constants.params.x;
+    rgb = (rgb - 0.5) * constants.params.y + 0.5;
+    let luma = dot(rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    rgb = mix(vec3<f32>(luma), rgb, constants.params.z);
+    rgb = pow(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(1.0 / constants.params.w));
+    return vec4<f32>(rgb, color.a);
+}
@@ -0,0 +1,35 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+struct Constants {
+    // Output size in pixels
+    resolution : vec2<f32>,
+    // Seconds on the engine clock
+    time : f32,
+    // Effect parameters, see `EffectKind::passes`
+    params : vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> constants : Constants;
+
+// Output of the scene or of the previous effect
+@group(0) @binding(0)
+var t_input: texture_2d<f32>;
+@group(0) @binding(1)
+var s_input: sampler;
+
+fn hash(p: vec3<f32>) -> f32 {
+    return fract(sin(dot(p, vec3<f32>(12.9898, 78.233, 37.719))) * 43758.5453);
+}
+
+// Per pixel noise changing 24 times a second: params.x is its strength.
+@fragment
+fn main_fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_input, s_input, input.tex_coords);
+    let seed = vec3<f32>(floor(input.clip_position.xy), floor(constants.time * 24.0));
+    let noise = hash(seed) - 0.5;
+    return vec4<f32>(clamp(color.rgb + noise * constants.params.x, vec3<f32>(0.0), vec3<f32>(1.0)), color.a);
+}
@@ -0,0 +1,36 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+struct Constants {
+    // Output size in pixels
+    resolution : vec2<f32>,
+    // Seconds on the engine clock
+    time : f32,
+    // Effect parameters, see `EffectKind::passes`
+    params : vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> constants : Constants;
+
+// Output of the scene or of the previous effect
+@group(0) @binding(0)
+var t_input: texture_2d<f32>;
+@group(0) @binding(1)
+var s_input: sampler;
+
+// Unsharp mask over the four direct neighbours: params.x is the strength.
+@fragment
+fn main_fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / constants.resolution;
+    let center = textureSample(t_input, s_input, input.tex_coords);
+    let neighbours = textureSample(t_input, s_input, input.tex_coords + vec2<f32>(texel.x, 0.0))
+        + textureSample(t_input, s_input, input.tex_coords - vec2<f32>(texel.x, 0.0))
+        + textureSample(t_input, s_input, input.tex_coords + vec2<f32>(0.0, texel.y))
+        + textureSample(t_input, s_input, input.tex_coords - vec2<f32>(0.0, texel.y));
+    let strength = constants.params.x;
+    let sharpened = center.rgb * (1.0 + 4.0 * strength) - neighbours.rgb * strength;
+    return vec4<f32>(clamp(sharpened, vec3<f32>(0.0), vec3<f32>(1.0)), center.a);
+}
@@ -0,0 +1,32 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+struct Constants {
+    // Output size in pixels
+    resolution : vec2<f32>,
+    // Seconds on the engine clock
+    time : f32,
+    // Effect parameters, see `EffectKind::passes`
+    params : vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> constants : Constants;
+
+// Output of the scene or of the previous effect
+@group(0) @binding(0)
+var t_input: texture_2d<f32>;
+@group(0) @binding(1)
+var s_input: sampler;
+
+// Darkens towards the corners: params.x is the strength, params.y the distance from the
+// center where darkening starts (1 at the corners) and params.z the width of the falloff.
+@fragment
+fn main_fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_input, s_input, input.tex_coords);
+    let distance = length(input.tex_coords - 0.5) * sqrt(2.0);
+    let shade = smoothstep(constants.params.y + constants.params.z, constants.params.y, distance);
+    return vec4<f32>(color.rgb * mix(1.0 - constants.params.x, 1.0, shade), color.a);
+}
@@ -0,0 +1,7 @@
+// Desaturate: effect.x is how far colors fade to gray.
+@fragment
+fn fs_effect(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let luma = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    return shade(vec4<f32>(mix(color.rgb, vec3<f32>(luma), image.effect.x), color.a));
+}
@@ -0,0 +1,12 @@
+// Hue shift: hues rotate by effect.x degrees plus effect.y degrees per second, around the
+// gray axis of RGB space.
+@fragment
+fn fs_effect(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let angle = radians(image.effect.x + image.effect.y * image.time);
+    let axis = vec3<f32>(0.57735026919);
+    let c = cos(angle);
+    let rgb = color.rgb * c + cross(axis, color.rgb) * sin(angle)
+        + axis * dot(axis, color.rgb) * (1.0 - c);
+    return shade(vec4<f32>(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0)), color.a));
+}
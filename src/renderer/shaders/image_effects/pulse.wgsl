@@ -0,0 +1,8 @@
+// Pulse: brightness swings by up to effect.x, effect.y times per second.
+@fragment
+fn fs_effect(in: VertexOutput) -> @location(0) vec4<f32> {
+    let tau = 6.28318530718;
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let brightness = 1.0 + image.effect.x * sin(image.time * image.effect.y * tau);
+    return shade(vec4<f32>(color.rgb * brightness, color.a));
+}
@@ -0,0 +1,12 @@
+// Ripple: effect.x is the amplitude in texture coordinates, effect.y the number of rings
+// from the center to an edge and effect.z how many rings spread per second.
+@fragment
+fn fs_effect(in: VertexOutput) -> @location(0) vec4<f32> {
+    let tau = 6.28318530718;
+    let from_center = in.tex_coords - vec2<f32>(0.5);
+    let distance = length(from_center) * 2.0;
+    let direction = from_center / max(distance, 0.0001);
+    let wave = sin((distance * image.effect.y - image.time * image.effect.z) * tau);
+    let offset = direction * image.effect.x * wave;
+    return shade(textureSample(t_diffuse, s_diffuse, in.tex_coords + offset));
+}
@@ -0,0 +1,7 @@
+// Scroll: effect.xy is the velocity in image sizes per second. The texture repeats, so the
+// image wraps around.
+@fragment
+fn fs_effect(in: VertexOutput) -> @location(0) vec4<f32> {
+    let offset = image.effect.xy * image.time;
+    return shade(textureSample(t_diffuse, s_diffuse, in.tex_coords - offset));
+}
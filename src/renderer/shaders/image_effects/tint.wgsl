@@ -0,0 +1,8 @@
+// Tint: effect.rgb is the color, effect.w how much of the image is recolored.
+@fragment
+fn fs_effect(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let luma = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    let rgb = mix(color.rgb, luma * image.effect.rgb, image.effect.w);
+    return shade(vec4<f32>(rgb, color.a));
+}
@@ -0,0 +1,9 @@
+// Waves: effect.x is the amplitude in texture coordinates, effect.y the number of waves
+// from top to bottom and effect.z how many times per second they sway.
+@fragment
+fn fs_effect(in: VertexOutput) -> @location(0) vec4<f32> {
+    let tau = 6.28318530718;
+    let phase = in.tex_coords.y * image.effect.y + image.time * image.effect.z;
+    let offset = vec2<f32>(image.effect.x * sin(phase * tau), 0.0);
+    return shade(textureSample(t_diffuse, s_diffuse, in.tex_coords + offset));
+}
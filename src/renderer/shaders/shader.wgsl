@@ -10,13 +10,28 @@ struct VertexOutput {
     @location(0) tex_coords: vec2<f32>,
 }
 
+struct Image {
+    model: mat4x4<f32>,
+    // RGB multiplied with the texture, alpha is the opacity
+    tint: vec4<f32>,
+    // Parameters of the image's effect, unused by `fs_main`
+    effect: vec4<f32>,
+    // Clock time in seconds
+    time: f32,
+    // 1.0 if the texture's colors are already multiplied by its alpha
+    premultiplied: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> image: Image;
+
 @vertex
 fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
     var out: VertexOutput;
     out.tex_coords = model.tex_coords;
-    out.clip_position = vec4<f32>(model.position, 1.0);
+    out.clip_position = image.model * vec4<f32>(model.position, 1.0);
     return out;
 }
 
@@ -27,7 +42,16 @@ var t_diffuse: texture_2d<f32>;
 @group(0)@binding(1)
 var s_diffuse: sampler;
 
+// Applies the tint and opacity, shared with the effects in `image_effects/`. Every blend
+// mode takes premultiplied colors.
+fn shade(color: vec4<f32>) -> vec4<f32> {
+    let alpha = color.a * image.tint.a;
+    let coverage = mix(color.a, 1.0, image.premultiplied) * image.tint.a;
+    return vec4<f32>(color.rgb * image.tint.rgb * coverage, alpha);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    var color: vec4<f32> = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    return shade(color);
 }
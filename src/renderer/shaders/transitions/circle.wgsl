@@ -0,0 +1,60 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+struct Constants {
+    // Eased progress from 0 to 1
+    progress : f32,
+    // Seconds since the transition started
+    time : f32,
+    // Output size in pixels
+    resolution : vec2<f32>,
+    // Preset parameters, see `TransitionKind::params`
+    params : vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> constants : Constants;
+
+// Outgoing scene
+@group(0) @binding(0)
+var t_from: texture_2d<f32>;
+@group(0)@binding(1)
+var s_diffuse: sampler;
+// Incoming scene
+@group(0) @binding(2)
+var t_to: texture_2d<f32>;
+
+@fragment
+fn main_fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+
+    // params.xy: center of the circle
+    var center: vec2<f32> = constants.params.xy;
+    // Measure in pixels so the circle stays round on any aspect ratio
+    var aspect: vec2<f32> = vec2<f32>(constants.resolution.x / constants.resolution.y, 1.0);
+
+    // Distance to the farthest corner, so the circle covers everything at the end
+    var r: f32 = max(
+        max(distance(center * aspect, vec2<f32>(0.0, 0.0)), distance(center * aspect, vec2<f32>(aspect.x, 0.0))),
+        max(distance(center * aspect, vec2<f32>(0.0, 1.0)), distance(center * aspect, aspect))
+    );
+    var timed_radius = r * constants.progress;
+
+    var point: vec2<f32> = input.tex_coords;
+    var from_color : vec4<f32> = textureSample(t_from, s_diffuse, input.tex_coords);
+    var to_color : vec4<f32> = textureSample(t_to, s_diffuse, input.tex_coords);
+
+
+    if (in_circle(center * aspect, point * aspect, timed_radius)) {
+        return to_color;
+    } else {
+        return from_color;
+    }
+
+}
+
+fn in_circle(center: vec2<f32>, point: vec2<f32>, radius: f32) -> bool {
+    var d = sqrt( pow(point.x - center.x, 2.0) + pow(point.y - center.y, 2.0));
+    return d < radius;
+}
\ No newline at end of file
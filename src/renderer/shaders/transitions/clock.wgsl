@@ -0,0 +1,46 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+struct Constants {
+    // Eased progress from 0 to 1
+    progress : f32,
+    // Seconds since the transition started
+    time : f32,
+    // Output size in pixels
+    resolution : vec2<f32>,
+    // Preset parameters, see `TransitionKind::params`
+    params : vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> constants : Constants;
+
+// Outgoing scene
+@group(0) @binding(0)
+var t_from: texture_2d<f32>;
+@group(0)@binding(1)
+var s_diffuse: sampler;
+// Incoming scene
+@group(0) @binding(2)
+var t_to: texture_2d<f32>;
+
+const PI: f32 = 3.14159265;
+
+// A hand sweeps clockwise from twelve o'clock, revealing the incoming scene
+@fragment
+fn main_fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+    // params.xy: center of the clock
+    var center: vec2<f32> = constants.params.xy;
+    var aspect: vec2<f32> = vec2<f32>(constants.resolution.x / constants.resolution.y, 1.0);
+    var offset: vec2<f32> = (input.tex_coords - center) * aspect;
+
+    // Angle from twelve o'clock, clockwise, from 0 to 1. Texture y grows downwards.
+    var angle: f32 = fract(atan2(offset.x, -offset.y) / (2.0 * PI) + 1.0);
+
+    var from_color : vec4<f32> = textureSample(t_from, s_diffuse, input.tex_coords);
+    var to_color : vec4<f32> = textureSample(t_to, s_diffuse, input.tex_coords);
+
+    return select(from_color, to_color, angle < constants.progress);
+}
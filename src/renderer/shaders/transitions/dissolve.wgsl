@@ -0,0 +1,56 @@
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+///////// FRAGMENT
+
+struct Constants {
+    // Eased progress from 0 to 1
+    progress : f32,
+    // Seconds since the transition started
+    time : f32,
+    // Output size in pixels
+    resolution : vec2<f32>,
+    // Preset parameters, see `TransitionKind::params`
+    params : vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> constants : Constants;
+
+// Outgoing scene
+@group(0) @binding(0)
+var t_from: texture_2d<f32>;
+@group(0)@binding(1)
+var s_diffuse: sampler;
+// Incoming scene
+@group(0) @binding(2)
+var t_to: texture_2d<f32>;
+
+// Helper function to generate a random number
+fn random_coord(co: vec2<f32>) -> f32 {
+    return fract(sin(dot(co, vec2(12.9898, 96.233))) * 43758.5453);
+}
+
+
+@fragment
+fn main_fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+    // params.x: size of a dissolving block in pixels
+    var block_size: f32 = max(constants.params.x, 1.0);
+    // params.y: share of the transition a block fades in over, 0 to switch at once
+    var fade: f32 = clamp(constants.params.y, 0.0, 1.0);
+    var texture_resolution : vec2<f32> = constants.resolution / block_size;
+    var pixel_within_texture : vec2<f32> = floor(input.tex_coords * texture_resolution);
+    var from_color : vec4<f32> = textureSample(t_from, s_diffuse, input.tex_coords);
+    var to_color : vec4<f32> = textureSample(t_to, s_diffuse, input.tex_coords);
+
+    var threshold: f32 = random_coord(pixel_within_texture);
+    if fade <= 0.0 {
+        return select(to_color, from_color, constants.progress < threshold);
+    }
+    // Blocks start fading early enough to be done by the end
+    var start: f32 = threshold * (1.0 - fade);
+    return mix(from_color, to_color, clamp((constants.progress - start) / fade, 0.0, 1.0));
+}
@@ -0,0 +1,41 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+struct Constants {
+    // Eased progress from 0 to 1
+    progress : f32,
+    // Seconds since the transition started
+    time : f32,
+    // Output size in pixels
+    resolution : vec2<f32>,
+    // Preset parameters, see `TransitionKind::params`
+    params : vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> constants : Constants;
+
+// Outgoing scene
+@group(0) @binding(0)
+var t_from: texture_2d<f32>;
+@group(0)@binding(1)
+var s_diffuse: sampler;
+// Incoming scene
+@group(0) @binding(2)
+var t_to: texture_2d<f32>;
+
+// The outgoing scene breaks into blocks, which resolve into the incoming scene
+@fragment
+fn main_fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+    // params.x: largest block size in pixels, reached halfway through
+    var block_size: f32 = max(constants.params.x * (1.0 - abs(2.0 * constants.progress - 1.0)), 1.0);
+    var blocks: vec2<f32> = constants.resolution / block_size;
+    var coords: vec2<f32> = (floor(input.tex_coords * blocks) + 0.5) / blocks;
+
+    var from_color : vec4<f32> = textureSample(t_from, s_diffuse, coords);
+    var to_color : vec4<f32> = textureSample(t_to, s_diffuse, coords);
+
+    return select(from_color, to_color, constants.progress >= 0.5);
+}
@@ -0,0 +1,45 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+struct Constants {
+    // Eased progress from 0 to 1
+    progress : f32,
+    // Seconds since the transition started
+    time : f32,
+    // Output size in pixels
+    resolution : vec2<f32>,
+    // Preset parameters, see `TransitionKind::params`
+    params : vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> constants : Constants;
+
+// Outgoing scene
+@group(0) @binding(0)
+var t_from: texture_2d<f32>;
+@group(0)@binding(1)
+var s_diffuse: sampler;
+// Incoming scene
+@group(0) @binding(2)
+var t_to: texture_2d<f32>;
+
+// A soft edge sweeps over the screen, revealing the incoming scene behind it
+@fragment
+fn main_fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+    // params.xy: direction the edge moves in
+    var direction: vec2<f32> = constants.params.xy;
+    var feather: f32 = 0.02;
+
+    // 0 where the edge starts, 1 where it ends
+    var position: f32 = dot(input.tex_coords - vec2<f32>(0.5, 0.5), direction) + 0.5;
+    // Move the edge slightly past both ends so it is fully off screen at 0 and 1
+    var edge: f32 = constants.progress * (1.0 + 2.0 * feather) - feather;
+
+    var from_color : vec4<f32> = textureSample(t_from, s_diffuse, input.tex_coords);
+    var to_color : vec4<f32> = textureSample(t_to, s_diffuse, input.tex_coords);
+
+    return mix(to_color, from_color, smoothstep(edge - feather, edge + feather, position));
+}
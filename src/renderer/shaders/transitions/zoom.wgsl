@@ -0,0 +1,47 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+struct Constants {
+    // Eased progress from 0 to 1
+    progress : f32,
+    // Seconds since the transition started
+    time : f32,
+    // Output size in pixels
+    resolution : vec2<f32>,
+    // Preset parameters, see `TransitionKind::params`
+    params : vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> constants : Constants;
+
+// Outgoing scene
+@group(0) @binding(0)
+var t_from: texture_2d<f32>;
+@group(0)@binding(1)
+var s_diffuse: sampler;
+// Incoming scene
+@group(0) @binding(2)
+var t_to: texture_2d<f32>;
+
+fn in_bounds(uv: vec2<f32>) -> bool {
+    return all(uv >= vec2<f32>(0.0, 0.0)) && all(uv <= vec2<f32>(1.0, 1.0));
+}
+
+// The incoming scene zooms in from half size while the outgoing one grows and fades out
+@fragment
+fn main_fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+    // params.xy: point both scenes zoom around
+    var origin: vec2<f32> = constants.params.xy;
+    var progress: f32 = constants.progress;
+
+    var from_coords: vec2<f32> = origin + (input.tex_coords - origin) / (1.0 + progress);
+    var to_coords: vec2<f32> = origin + (input.tex_coords - origin) / mix(0.5, 1.0, progress);
+
+    var from_color : vec4<f32> = textureSample(t_from, s_diffuse, from_coords);
+    var to_color : vec4<f32> = textureSample(t_to, s_diffuse, to_coords);
+
+    return mix(from_color, select(from_color, to_color, in_bounds(to_coords)), progress);
+}
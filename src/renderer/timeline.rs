@@ -0,0 +1,237 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// Shape of a transition's progress over time. Written in manifests as a string, e.g.
+/// `easing = "ease_in_out"`, or as a table for the parameterised curves:
+/// `easing = { steps = 4 }` and `easing = { cubic_bezier = [0.25, 0.1, 0.25, 1.0] }`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Cubic, starting slow.
+    EaseIn,
+    /// Cubic, ending slow.
+    EaseOut,
+    /// Cubic, slow at both ends.
+    EaseInOut,
+    /// Overshoots the end and settles on it, like a spring.
+    Elastic,
+    /// Jumps in this many equal steps.
+    Steps(u32),
+    /// CSS-style `cubic-bezier(x1, y1, x2, y2)`. Both x values must be between 0 and 1.
+    CubicBezier([f32; 4]),
+}
+
+impl Easing {
+    /// Maps linear progress `t` in 0..=1 to eased progress. The result starts at 0 and ends
+    /// at 1, but may leave that range in between.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match *self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::Elastic => {
+                if t == 0.0 || t == 1.0 {
+                    return t;
+                }
+                let c4 = 2.0 * std::f32::consts::PI / 3.0;
+                2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * c4).sin() + 1.0
+            }
+            Easing::Steps(steps) => {
+                let steps = steps.max(1) as f32;
+                (t * steps).floor() / steps
+            }
+            Easing::CubicBezier([x1, y1, x2, y2]) => {
+                let s = solve_bezier(t, x1, x2);
+                bezier(s, y1, y2)
+            }
+        }
+    }
+
+    /// Checks the parameters serde can't.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match *self {
+            Easing::Steps(0) => anyhow::bail!("`steps` must be at least 1"),
+            Easing::CubicBezier(points) if points.iter().any(|p| !p.is_finite()) => {
+                anyhow::bail!("`cubic_bezier` points must be finite numbers")
+            }
+            Easing::CubicBezier([x1, _, x2, _])
+                if !(0.0..=1.0).contains(&x1) || !(0.0..=1.0).contains(&x2) =>
+            {
+                anyhow::bail!("`cubic_bezier` x values must be between 0.0 and 1.0")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// One coordinate of a cubic bezier from 0 to 1 with control points `p1` and `p2`.
+fn bezier(s: f32, p1: f32, p2: f32) -> f32 {
+    let inv = 1.0 - s;
+    3.0 * inv * inv * s * p1 + 3.0 * inv * s * s * p2 + s * s * s
+}
+
+/// Finds the curve parameter whose x coordinate is `x`. x is monotonic because both
+/// control points lie within 0..=1, so bisection always converges.
+fn solve_bezier(x: f32, x1: f32, x2: f32) -> f32 {
+    let (mut low, mut high) = (0.0, 1.0);
+    let mut s = x;
+    for _ in 0..32 {
+        let current = bezier(s, x1, x2);
+        if (current - x).abs() < 1e-5 {
+            break;
+        }
+        if current < x {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = (low + high) / 2.0;
+    }
+    s
+}
+
+/// Maps clock time since a start point to progress through an [`Easing`] curve. Every
+/// method takes the current clock reading.
+#[derive(Debug, Clone, Copy)]
+pub struct Timeline {
+    pub started: Duration,
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+impl Timeline {
+    pub fn new(started: Duration, duration: Duration, easing: Easing) -> Self {
+        Timeline {
+            started,
+            duration,
+            easing,
+        }
+    }
+
+    pub fn elapsed(&self, now: Duration) -> Duration {
+        now.saturating_sub(self.started)
+    }
+
+    /// Linear progress from 0 to 1.
+    pub fn progress(&self, now: Duration) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        (self.elapsed(now).as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+
+    /// Progress through the easing curve. Exactly 1 once the timeline is finished.
+    pub fn eased_progress(&self, now: Duration) -> f32 {
+        self.easing.apply(self.progress(now))
+    }
+
+    pub fn is_finished(&self, now: Duration) -> bool {
+        self.progress(now) >= 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [Easing; 7] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::Elastic,
+        Easing::Steps(3),
+        Easing::CubicBezier([0.25, 0.1, 0.25, 1.0]),
+    ];
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn curves_start_at_0_and_end_at_1() {
+        for easing in CURVES {
+            assert_near(easing.apply(0.0), 0.0);
+            assert_near(easing.apply(1.0), 1.0);
+            // Out of range progress is clamped
+            assert_near(easing.apply(-0.5), 0.0);
+            assert_near(easing.apply(1.5), 1.0);
+        }
+    }
+
+    #[test]
+    fn curves_have_their_shape() {
+        assert_near(Easing::EaseIn.apply(0.5), 0.125);
+        assert_near(Easing::EaseOut.apply(0.5), 0.875);
+        assert_near(Easing::EaseInOut.apply(0.5), 0.5);
+        assert!((1..100).any(|i| Easing::Elastic.apply(i as f32 / 100.0) > 1.0));
+
+        let steps = Easing::Steps(4);
+        assert_near(steps.apply(0.24), 0.0);
+        assert_near(steps.apply(0.25), 0.25);
+        assert_near(steps.apply(0.99), 0.75);
+
+        // Control points on the diagonal make a straight line
+        let line = Easing::CubicBezier([0.3, 0.3, 0.7, 0.7]);
+        for t in [0.1, 0.5, 0.9] {
+            assert_near(line.apply(t), t);
+        }
+    }
+
+    #[test]
+    fn validate_rejects_what_serde_accepts() {
+        assert!(Easing::Steps(0).validate().is_err());
+        assert!(Easing::CubicBezier([1.5, 0.0, 0.5, 1.0])
+            .validate()
+            .is_err());
+        assert!(Easing::CubicBezier([0.5, f32::NAN, 0.5, 1.0])
+            .validate()
+            .is_err());
+        // y values may overshoot
+        assert!(Easing::CubicBezier([0.5, -1.0, 0.5, 2.0])
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn timelines_follow_the_clock_and_stop_at_the_end() {
+        let timeline = Timeline::new(
+            Duration::from_secs(10),
+            Duration::from_secs(2),
+            Easing::EaseIn,
+        );
+        // Readings before the start count as the start
+        assert_near(timeline.progress(Duration::from_secs(9)), 0.0);
+        assert_near(timeline.progress(Duration::from_secs(11)), 0.5);
+        assert_near(timeline.eased_progress(Duration::from_secs(11)), 0.125);
+        assert!(!timeline.is_finished(Duration::from_millis(11_999)));
+
+        assert!(timeline.is_finished(Duration::from_secs(12)));
+        assert_eq!(timeline.eased_progress(Duration::from_secs(60)), 1.0);
+        assert_eq!(
+            timeline.elapsed(Duration::from_secs(60)),
+            Duration::from_secs(50)
+        );
+    }
+
+    #[test]
+    fn zero_length_timelines_finish_immediately() {
+        let timeline = Timeline::new(Duration::from_secs(1), Duration::ZERO, Easing::Elastic);
+        assert!(timeline.is_finished(Duration::ZERO));
+        assert_eq!(timeline.eased_progress(Duration::from_secs(1)), 1.0);
+    }
+}
@@ -1,149 +1,528 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{bail, Context};
+use rand::seq::SliceRandom;
+use serde::Deserialize;
 use wgpu::{
-    util::DeviceExt, CommandEncoder, Device, RenderPipeline, SurfaceConfiguration, TextureFormat,
-    TextureView,
+    util::DeviceExt, CommandEncoder, Device, Queue, RenderPipeline, SurfaceConfiguration,
+    TextureFormat, TextureView,
 };
 
 use super::{
     primitives::{plane::Plane, vertex::Vertex},
     scenes::scene::{Scene, SceneType},
+    timeline::{Easing, Timeline},
+    validation,
 };
 
 pub struct Transition {
-    pub transition_uniform: TransitionUniform,
-    pub transition_buffer: wgpu::Buffer,
-    pub transition_bind_group: wgpu::BindGroup,
-    pub transition_pipeline: wgpu::RenderPipeline,
-    pub sampler: wgpu::Sampler,
-    pub plane: Plane,
-    pub vertex_buffer: Option<wgpu::Buffer>,
-    pub index_buffer: Option<wgpu::Buffer>,
-    pub bind_group: Option<wgpu::BindGroup>,
-    pub time_started: std::time::Instant,
-
-    pub scene: SceneType,
-    pub scene_texture: wgpu::Texture,
+    transition_uniform: TransitionUniform,
+    transition_buffer: wgpu::Buffer,
+    transition_bind_group: wgpu::BindGroup,
+    pipelines: Arc<TransitionPipelines>,
+    transition_pipeline: Arc<RenderPipeline>,
+    sampler: wgpu::Sampler,
+    plane: Plane,
+    vertex_buffer: Option<wgpu::Buffer>,
+    index_buffer: Option<wgpu::Buffer>,
+    bind_group: Option<wgpu::BindGroup>,
+    timeline: Timeline,
+    /// Size of the render targets relative to the output, see [`Engine::transition_quality`].
+    ///
+    /// [`Engine::transition_quality`]: super::engine::Engine::transition_quality
+    quality: f32,
+
+    /// The outgoing scene. The incoming one stays owned by the engine.
+    scene: SceneType,
+    from_texture: wgpu::Texture,
+    to_texture: wgpu::Texture,
 }
 
-#[repr(C)]
-#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct TransitionUniform {
-    pub time_offset: f32,
-    pub dissolve_speed: f32,
+/// The built-in transition presets, each backed by a shader in `shaders/transitions/`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionKind {
+    /// Crossfade between both scenes.
+    Fade,
+    /// Blocks of `size` pixels switch to the incoming scene in random order, fading in at
+    /// `speed` if set.
+    Dissolve,
+    /// Circle growing from `origin` until it covers the screen.
+    Circle,
+    /// The incoming scene slides in over the outgoing one towards `direction`.
+    Slide,
+    /// The incoming scene pushes the outgoing one out towards `direction`.
+    Push,
+    /// A soft edge sweeps across the screen towards `direction`.
+    Wipe,
+    /// The incoming scene zooms in around `origin`.
+    Zoom,
+    /// Both scenes break into blocks of up to `size` pixels.
+    Pixelate,
+    /// `count` slats open towards `direction`.
+    Blinds,
+    /// A clock hand sweeps around `origin`, starting at twelve.
+    Clock,
 }
 
-impl TransitionUniform {
-    pub fn update_time_offset(&mut self, time: f32) {
-        self.time_offset = time;
+impl TransitionKind {
+    pub const ALL: [TransitionKind; 10] = [
+        TransitionKind::Fade,
+        TransitionKind::Dissolve,
+        TransitionKind::Circle,
+        TransitionKind::Slide,
+        TransitionKind::Push,
+        TransitionKind::Wipe,
+        TransitionKind::Zoom,
+        TransitionKind::Pixelate,
+        TransitionKind::Blinds,
+        TransitionKind::Clock,
+    ];
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            TransitionKind::Fade => "fade",
+            TransitionKind::Dissolve => "dissolve",
+            TransitionKind::Circle => "circle",
+            TransitionKind::Slide => "slide",
+            TransitionKind::Push => "push",
+            TransitionKind::Wipe => "wipe",
+            TransitionKind::Zoom => "zoom",
+            TransitionKind::Pixelate => "pixelate",
+            TransitionKind::Blinds => "blinds",
+            TransitionKind::Clock => "clock",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    pub fn random() -> Self {
+        *Self::ALL.choose(&mut rand::thread_rng()).unwrap()
+    }
+
+    pub fn shader_source(&self) -> &'static str {
+        match self {
+            TransitionKind::Fade => include_str!("../renderer/shaders/transitions/fade.wgsl"),
+            TransitionKind::Dissolve => {
+                include_str!("../renderer/shaders/transitions/dissolve.wgsl")
+            }
+            TransitionKind::Circle => include_str!("../renderer/shaders/transitions/circle.wgsl"),
+            TransitionKind::Slide => include_str!("../renderer/shaders/transitions/slide.wgsl"),
+            TransitionKind::Push => include_str!("../renderer/shaders/transitions/push.wgsl"),
+            TransitionKind::Wipe => include_str!("../renderer/shaders/transitions/wipe.wgsl"),
+            TransitionKind::Zoom => include_str!("../renderer/shaders/transitions/zoom.wgsl"),
+            TransitionKind::Pixelate => {
+                include_str!("../renderer/shaders/transitions/pixelate.wgsl")
+            }
+            TransitionKind::Blinds => include_str!("../renderer/shaders/transitions/blinds.wgsl"),
+            TransitionKind::Clock => include_str!("../renderer/shaders/transitions/clock.wgsl"),
+        }
+    }
+
+    /// The `[transition]` keys this preset reads, besides `name` and `duration`.
+    pub fn parameters(&self) -> &'static [&'static str] {
+        match self {
+            TransitionKind::Fade => &[],
+            TransitionKind::Dissolve => &["size", "speed"],
+            TransitionKind::Pixelate => &["size"],
+            TransitionKind::Circle | TransitionKind::Zoom | TransitionKind::Clock => &["origin"],
+            TransitionKind::Slide | TransitionKind::Push | TransitionKind::Wipe => &["direction"],
+            TransitionKind::Blinds => &["direction", "count"],
+        }
+    }
+
+    /// Packs the settings into the shader's `params`, filling in defaults for unset keys.
+    pub fn params(&self, settings: &TransitionSettings) -> [f32; 4] {
+        let origin = |default| settings.origin.unwrap_or(default);
+        let direction = |default| settings.direction.unwrap_or(default).vector();
+
+        match self {
+            TransitionKind::Fade => [0.0; 4],
+            TransitionKind::Dissolve => {
+                // The shader reads the share of the duration a block fades over, 0 to snap
+                let fade = settings.speed.map_or(0.0, |speed| 1.0 / speed);
+                [settings.size.unwrap_or(8.0), fade, 0.0, 0.0]
+            }
+            TransitionKind::Pixelate => [settings.size.unwrap_or(48.0), 0.0, 0.0, 0.0],
+            TransitionKind::Circle => {
+                let [x, y] = origin([1.0, 1.0]);
+                [x, y, 0.0, 0.0]
+            }
+            TransitionKind::Zoom | TransitionKind::Clock => {
+                let [x, y] = origin([0.5, 0.5]);
+                [x, y, 0.0, 0.0]
+            }
+            TransitionKind::Slide | TransitionKind::Push => {
+                let [x, y] = direction(Direction::Left);
+                [x, y, 0.0, 0.0]
+            }
+            TransitionKind::Wipe => {
+                let [x, y] = direction(Direction::Right);
+                [x, y, 0.0, 0.0]
+            }
+            TransitionKind::Blinds => {
+                let [x, y] = direction(Direction::Right);
+                [x, y, settings.count.unwrap_or(10) as f32, 0.0]
+            }
+        }
     }
 }
 
-impl Transition {
-    pub fn test(
-        device: &Device,
-        config: &SurfaceConfiguration,
-        scene: SceneType,
-        format: TextureFormat,
-    ) -> Self {
-        let transition_uniform = TransitionUniform {
-            dissolve_speed: 0.5,
-            time_offset: 0.0,
+/// The `name` of a transition: a preset, or `"random"` to pick a different preset on
+/// every scene change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionName {
+    Random,
+    Preset(TransitionKind),
+}
+
+impl<'de> Deserialize<'de> for TransitionName {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        const NAMES: [&str; TransitionKind::ALL.len() + 1] = {
+            let mut names = ["random"; TransitionKind::ALL.len() + 1];
+            let mut i = 0;
+            while i < TransitionKind::ALL.len() {
+                names[i + 1] = TransitionKind::ALL[i].name();
+                i += 1;
+            }
+            names
         };
 
-        let transition_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Transition buffer"),
-            contents: bytemuck::cast_slice(&[transition_uniform]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
+        let name = String::deserialize(deserializer)?;
+        match name.as_str() {
+            "random" => Ok(TransitionName::Random),
+            _ => TransitionKind::from_name(&name)
+                .map(TransitionName::Preset)
+                .ok_or_else(|| serde::de::Error::unknown_variant(&name, &NAMES)),
+        }
+    }
+}
 
-        let transition_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("transition uniform bind group layout"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }],
-            });
+impl std::fmt::Display for TransitionName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransitionName::Random => f.write_str("random"),
+            TransitionName::Preset(kind) => f.write_str(kind.name()),
+        }
+    }
+}
 
-        let transition_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("a"),
-            layout: &transition_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: transition_buffer.as_entire_binding(),
-            }],
-        });
+/// Screen direction a transition moves in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
 
-        let plane = Plane::new(1.0);
+impl Direction {
+    /// Unit vector in texture coordinates, where y grows downwards.
+    pub fn vector(&self) -> [f32; 2] {
+        match self {
+            Direction::Left => [-1.0, 0.0],
+            Direction::Right => [1.0, 0.0],
+            Direction::Up => [0.0, -1.0],
+            Direction::Down => [0.0, 1.0],
+        }
+    }
+}
 
-        let time_started = std::time::Instant::now();
+/// Which transition to play and for how long, as written in a manifest's `[transition]`
+/// table. Exactly one of `name` and `shader` is set, and only the parameters the chosen
+/// preset reads (see [`TransitionKind::parameters`]) may be.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransitionSettings {
+    /// A built-in transition, or `"random"`.
+    #[serde(default)]
+    pub name: Option<TransitionName>,
+    /// Path to a user-defined transition shader, see [`TransitionShader`].
+    #[serde(default)]
+    pub shader: Option<PathBuf>,
+    /// Length of the transition in seconds.
+    #[serde(default = "default_duration")]
+    pub duration: f32,
+    /// How progress moves over the duration.
+    #[serde(default)]
+    pub easing: Easing,
 
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
+    /// Block size in pixels.
+    #[serde(default)]
+    pub size: Option<f32>,
+    /// How fast each block fades in once its turn comes, relative to the whole transition:
+    /// 1 fades every block over the full duration, 4 over a quarter of it. Blocks switch at
+    /// once when unset. The duration still sets when the last block is done.
+    #[serde(default)]
+    pub speed: Option<f32>,
+    /// Point the transition is centered on, from (0, 0) at the top left to (1, 1) at the
+    /// bottom right.
+    #[serde(default)]
+    pub origin: Option<[f32; 2]>,
+    #[serde(default)]
+    pub direction: Option<Direction>,
+    /// Number of slats.
+    #[serde(default)]
+    pub count: Option<u32>,
+    /// Raw `params` passed to a user-defined shader.
+    #[serde(default)]
+    pub params: Option<[f32; 4]>,
+}
+
+fn default_duration() -> f32 {
+    1.0
+}
+
+impl TransitionSettings {
+    /// Checks the duration, that exactly one of `name` and `shader` is set and the
+    /// parameters the chosen one reads.
+    pub fn validate(&self, key: &str) -> anyhow::Result<()> {
+        if !self.duration.is_finite() || self.duration < 0.0 {
+            bail!("`{key}.duration`: must be a non-negative number of seconds");
+        }
+        let accepted = match (&self.name, &self.shader) {
+            (Some(TransitionName::Preset(kind)), None) => kind.parameters(),
+            (Some(TransitionName::Random), None) => &[],
+            (None, Some(shader)) => {
+                if !shader.is_file() {
+                    bail!("`{key}.shader`: file `{}` does not exist", shader.display());
+                }
+                &["params"]
+            }
+            _ => bail!("`{key}`: exactly one of `name` and `shader` must be set"),
+        };
+        let owner = match &self.name {
+            Some(name) => format!("`{name}`"),
+            None => "custom shaders".to_string(),
+        };
+        let value = |value: Option<f32>| value.map(|value| vec![value]);
+        validation::validate_parameters(
+            key,
+            &owner,
+            accepted,
+            vec![
+                ("size", value(self.size)),
+                ("speed", value(self.speed)),
+                ("origin", self.origin.map(Vec::from)),
+                ("direction", self.direction.map(|_| Vec::new())),
+                ("count", value(self.count.map(|count| count as f32))),
+                ("params", self.params.map(Vec::from)),
+            ],
+        )?;
+        if self.size.is_some_and(|size| size < 1.0) {
+            bail!("`{key}.size`: must be at least 1 pixel");
+        }
+        if self.speed.is_some_and(|speed| speed < 1.0) {
+            bail!("`{key}.speed`: must be at least 1.0");
+        }
+        if self.count == Some(0) {
+            bail!("`{key}.count`: must be at least 1");
+        }
+        if let Err(e) = self.easing.validate() {
+            bail!("`{key}.easing`: {e}");
+        }
+
+        Ok(())
+    }
+
+    pub fn new(name: TransitionKind) -> Self {
+        TransitionSettings {
+            name: Some(TransitionName::Preset(name)),
+            shader: None,
+            duration: default_duration(),
+            easing: Easing::default(),
+            size: None,
+            speed: None,
+            origin: None,
+            direction: None,
+            count: None,
+            params: None,
+        }
+    }
+
+    /// Resolves the settings into the transition to play: picks a preset for `"random"`,
+    /// or reads and validates the user-defined shader.
+    pub fn load(&self) -> anyhow::Result<TransitionSpec> {
+        let (shader, params) = match (&self.name, &self.shader) {
+            (Some(name), None) => {
+                let kind = match name {
+                    TransitionName::Random => TransitionKind::random(),
+                    TransitionName::Preset(kind) => *kind,
+                };
+                (TransitionShader::builtin(kind), kind.params(self))
+            }
+            (None, Some(path)) => (
+                TransitionShader::from_path(path).context("`shader`")?,
+                self.params.unwrap_or_default(),
+            ),
+            _ => anyhow::bail!("exactly one of `name` and `shader` must be set"),
+        };
+
+        Ok(TransitionSpec {
+            shader,
+            params,
+            duration: self.duration(),
+            easing: self.easing,
+        })
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f32(self.duration.max(0.0))
+    }
+}
+
+/// A transition ready to be played: its shader, the `params` passed to it and its timing.
+#[derive(Debug, Clone)]
+pub struct TransitionSpec {
+    pub shader: TransitionShader,
+    pub params: [f32; 4],
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+/// WGSL source of a transition that passed validation, ready for pipeline creation.
+///
+/// The engine supplies the vertex stage, drawing a full screen quad. A transition shader
+/// provides the fragment stage and must follow this contract:
+///
+/// - The entry point is `@fragment fn main_fragment`, returning the color at `@location(0)`.
+/// - Its only input is `@location(0) tex_coords: vec2<f32>`, from (0, 0) at the top left
+///   to (1, 1) at the bottom right. `@builtin(position)` may be read as well.
+/// - It may declare any of these bindings, and no others:
+///   - `@group(0) @binding(0) var t_from: texture_2d<f32>` — the outgoing scene
+///   - `@group(0) @binding(1) var s_diffuse: sampler`
+///   - `@group(0) @binding(2) var t_to: texture_2d<f32>` — the incoming scene
+///   - `@group(1) @binding(0) var<uniform> constants: Constants`, where `Constants` is
+///     `{ progress: f32, time: f32, resolution: vec2<f32>, params: vec4<f32> }` or a prefix
+///     of it. `progress` is eased and goes from 0 to 1 (elastic easing overshoots in
+///     between), `time` is in seconds since the start,
+///     `resolution` is the output size in pixels and `params` holds the manifest's
+///     `params`, or zeros.
+#[derive(Debug, Clone)]
+pub struct TransitionShader {
+    pub label: String,
+    pub source: Cow<'static, str>,
+    /// The preset this is the shader of, `None` for user-defined shaders.
+    pub kind: Option<TransitionKind>,
+}
+
+impl TransitionShader {
+    pub fn builtin(kind: TransitionKind) -> Self {
+        TransitionShader {
+            label: format!("{} transition", kind.name()),
+            source: Cow::Borrowed(kind.shader_source()),
+            kind: Some(kind),
+        }
+    }
+
+    pub fn from_path(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let source = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read `{}`", path.display()))?;
+        Self::from_source(path.display().to_string(), source)
+    }
+
+    pub fn from_source(label: String, source: String) -> anyhow::Result<Self> {
+        validation::validate_transition_shader(&label, &source)?;
+        Ok(TransitionShader {
+            label,
+            source: Cow::Owned(source),
+            kind: None,
+        })
+    }
+}
+
+/// Render pipelines of the built-in transitions, compiled the first time a preset plays and
+/// shared by every transition playing it afterwards. User-defined shaders are compiled
+/// whenever they start, so edits to them show. All of them share one pipeline layout.
+pub struct TransitionPipelines {
+    format: TextureFormat,
+    texture_layout: wgpu::BindGroupLayout,
+    uniform_layout: wgpu::BindGroupLayout,
+    layout: wgpu::PipelineLayout,
+    vertex_shader: wgpu::ShaderModule,
+    pipelines: Mutex<HashMap<TransitionKind, Arc<RenderPipeline>>>,
+}
 
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Transition shader"),
+impl TransitionPipelines {
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        let texture_layout = Self::texture_bind_group_layout(device);
+        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("transition uniform bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Transition render layout"),
+            bind_group_layouts: &[&texture_layout, &uniform_layout],
+            push_constant_ranges: &[],
+        });
+        let vertex_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Transition vertex shader"),
             source: wgpu::ShaderSource::Wgsl(
-                include_str!("../renderer/shaders/transition2.wgsl").into(),
+                include_str!("../renderer/shaders/transition_vertex.wgsl").into(),
             ),
         });
 
-        let texture_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            multisampled: false,
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                ],
-                label: Some("texture_bind_group_layout"),
-            });
-
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Transition render layout"),
-                bind_group_layouts: &[&texture_bind_group_layout, &transition_bind_group_layout],
-                push_constant_ranges: &[],
-            });
-
-        let transition_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        TransitionPipelines {
+            format,
+            texture_layout,
+            uniform_layout,
+            layout,
+            vertex_shader,
+            pipelines: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The pipeline drawing `shader`, created on first use for the presets.
+    pub fn get(&self, device: &Device, shader: &TransitionShader) -> Arc<RenderPipeline> {
+        let Some(kind) = shader.kind else {
+            return Arc::new(self.create_pipeline(device, shader));
+        };
+        let mut pipelines = self.pipelines.lock().unwrap();
+        pipelines
+            .entry(kind)
+            .or_insert_with(|| Arc::new(self.create_pipeline(device, shader)))
+            .clone()
+    }
+
+    fn create_pipeline(&self, device: &Device, shader: &TransitionShader) -> RenderPipeline {
+        let fragment_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&shader.label),
+            source: wgpu::ShaderSource::Wgsl(shader.source.clone()),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Transition pipeline"),
-            layout: Some(&render_pipeline_layout),
+            layout: Some(&self.layout),
             vertex: wgpu::VertexState {
-                module: &shader,
+                module: &self.vertex_shader,
                 entry_point: "main_vertex",
                 buffers: &[Vertex::desc()],
             },
             fragment: Some(wgpu::FragmentState {
-                module: &shader,
+                module: &fragment_shader,
                 entry_point: "main_fragment",
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    format: self.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
             }),
@@ -163,41 +542,270 @@ impl Transition {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
-        });
+        })
+    }
 
-        let scene_texture = device.create_texture(&wgpu::TextureDescriptor {
-            // Set the dimensions and format of the texture
-            size: wgpu::Extent3d {
-                width: 500,  // Replace with the actual width of your surface
-                height: 500, // Replace with the actual height of your surface
-                depth_or_array_layers: 1,
+    /// Layout of group 0 in transition shaders: the outgoing scene at binding 0, the
+    /// sampler at binding 1 and the incoming scene at binding 2.
+    fn texture_bind_group_layout(device: &Device) -> wgpu::BindGroupLayout {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
             },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-            label: Some("Render Texture"),
-            view_formats: &[],
+            count: None,
+        };
+
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                texture_entry(0),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    // This should match the filterable field of the
+                    // corresponding Texture entry above.
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                texture_entry(2),
+            ],
+            label: Some("transition_texture_bind_group_layout"),
+        })
+    }
+}
+
+/// What happens when a transition is started while another one is still running.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InterruptPolicy {
+    /// The running transition jumps to its end and the new one starts right away.
+    #[default]
+    Snap,
+    /// The new transition is queued and starts once the running one has finished.
+    Chain,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TransitionUniform {
+    /// Eased progress of the transition, from 0 to 1.
+    pub progress: f32,
+    /// Seconds since the transition started.
+    pub time: f32,
+    /// Size of the output in pixels.
+    pub resolution: [f32; 2],
+    /// Preset parameters, see [`TransitionKind::params`].
+    pub params: [f32; 4],
+}
+
+impl TransitionUniform {
+    pub fn update_time(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    pub fn update_progress(&mut self, progress: f32) {
+        self.progress = progress;
+    }
+}
+
+impl Transition {
+    /// Starts a transition away from `scene` at `started`, ready to draw. `quality` is
+    /// clamped to 0.1..=1.0.
+    pub fn start(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        pipelines: &Arc<TransitionPipelines>,
+        scene: SceneType,
+        spec: &TransitionSpec,
+        quality: f32,
+        started: Duration,
+    ) -> Self {
+        let mut transition = Transition::new(
+            device,
+            config,
+            pipelines,
+            scene,
+            spec,
+            quality.clamp(0.1, 1.0),
+            started,
+        );
+        transition.create_buffers(device);
+        transition
+    }
+
+    /// Starts the transition a scene switches between the scenes it holds with, from the
+    /// `settings` found at `key` in the manifest. Settings that fail to load are logged and
+    /// the switch happens without a transition.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn start_nested(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        pipelines: &Arc<TransitionPipelines>,
+        scene: SceneType,
+        settings: &TransitionSettings,
+        key: &str,
+        quality: f32,
+        started: Duration,
+    ) -> Option<Box<Self>> {
+        match settings.load() {
+            Ok(spec) => Some(Box::new(Transition::start(
+                device, config, pipelines, scene, &spec, quality, started,
+            ))),
+            Err(e) => {
+                log::warn!("`{key}`: {e:#}");
+                None
+            }
+        }
+    }
+
+    fn new(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        pipelines: &Arc<TransitionPipelines>,
+        scene: SceneType,
+        spec: &TransitionSpec,
+        quality: f32,
+        started: Duration,
+    ) -> Self {
+        let transition_uniform = TransitionUniform {
+            progress: 0.0,
+            time: 0.0,
+            resolution: [config.width as f32, config.height as f32],
+            params: spec.params,
+        };
+
+        let transition_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Transition buffer"),
+            contents: bytemuck::cast_slice(&[transition_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let transition_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("a"),
+            layout: &pipelines.uniform_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: transition_buffer.as_entire_binding(),
+            }],
         });
 
+        let plane = Plane::new(1.0);
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let transition_pipeline = pipelines.get(device, &spec.shader);
+
+        let from_texture =
+            Self::create_render_texture(device, config, quality, "Transition from texture");
+        let to_texture =
+            Self::create_render_texture(device, config, quality, "Transition to texture");
+
         Self {
             transition_uniform,
             transition_buffer,
             transition_bind_group,
             plane,
-            time_started,
+            timeline: Timeline::new(started, spec.duration, spec.easing),
+            quality,
             bind_group: None,
             index_buffer: None,
             vertex_buffer: None,
             sampler,
+            pipelines: pipelines.clone(),
             transition_pipeline,
             scene,
-            scene_texture,
+            from_texture,
+            to_texture,
         }
     }
 
-    pub fn create_vertex_buffer(&mut self, device: &Device) {
+    /// Creates the GPU buffers and bind group needed to draw the transition.
+    fn create_buffers(&mut self, device: &Device) {
+        self.create_bind_group(device);
+        self.create_index_buffer(device);
+        self.create_vertex_buffer(device);
+    }
+
+    /// Uploads the time and eased progress at `now`, a clock reading.
+    pub fn update(&mut self, queue: &Queue, now: Duration) {
+        let timeline = self.timeline;
+        self.transition_uniform
+            .update_time(timeline.elapsed(now).as_secs_f32());
+        self.transition_uniform
+            .update_progress(timeline.eased_progress(now));
+
+        queue.write_buffer(
+            &self.transition_buffer,
+            0,
+            bytemuck::cast_slice(&[self.transition_uniform]),
+        );
+    }
+
+    /// The scene being transitioned away from.
+    pub fn scene(&self) -> &SceneType {
+        &self.scene
+    }
+
+    pub fn scene_mut(&mut self) -> &mut SceneType {
+        &mut self.scene
+    }
+
+    /// How far the transition has run, from 0 to 1, before easing.
+    pub fn progress(&self, now: Duration) -> f32 {
+        self.timeline.progress(now)
+    }
+
+    pub fn is_finished(&self, now: Duration) -> bool {
+        self.timeline.is_finished(now)
+    }
+
+    /// Follows a resized output: updates `resolution` and recreates both render targets and
+    /// the bind group reading them.
+    pub fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        self.transition_uniform.resolution = [config.width as f32, config.height as f32];
+        self.from_texture =
+            Self::create_render_texture(device, config, self.quality, "Transition from texture");
+        self.to_texture =
+            Self::create_render_texture(device, config, self.quality, "Transition to texture");
+        self.create_bind_group(device);
+    }
+
+    /// A render target matching the output size, scaled by `quality`.
+    fn create_render_texture(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        quality: f32,
+        label: &str,
+    ) -> wgpu::Texture {
+        let scaled = |size: u32| ((size as f32 * quality).round() as u32).max(1);
+
+        device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: scaled(config.width),
+                height: scaled(config.height),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            label: Some(label),
+            view_formats: &[],
+        })
+    }
+
+    fn create_vertex_buffer(&mut self, device: &Device) {
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Image vertex buffer"),
             contents: bytemuck::cast_slice(&self.plane.vertices),
@@ -206,7 +814,7 @@ impl Transition {
         self.vertex_buffer = Some(vertex_buffer);
     }
 
-    pub fn create_index_buffer(&mut self, device: &Device) {
+    fn create_index_buffer(&mut self, device: &Device) {
         let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Image 9ndex buffer"),
             contents: bytemuck::cast_slice(&self.plane.get_indices()),
@@ -215,64 +823,72 @@ impl Transition {
         self.index_buffer = Some(index_buffer);
     }
 
-    pub fn create_bind_group(&mut self, device: &Device, texture: &wgpu::TextureView) {
-        let texture_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            multisampled: false,
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        // This should match the filterable field of the
-                        // corresponding Texture entry above.
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                ],
-                label: Some("texture_bind_group_layout"),
-            });
-
-        let diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &texture_bind_group_layout,
+    fn create_bind_group(&mut self, device: &Device) {
+        let from_view = self
+            .from_texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let to_view = self
+            .to_texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.pipelines.texture_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(texture),
+                    resource: wgpu::BindingResource::TextureView(&from_view),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
                     resource: wgpu::BindingResource::Sampler(&self.sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&to_view),
+                },
             ],
             label: Some("transition bind group"),
         });
 
-        self.bind_group = Some(diffuse_bind_group);
+        self.bind_group = Some(bind_group);
     }
 
+    /// Renders the outgoing scene and the incoming scene `to` into their own textures and
+    /// composes them into `view` with the transition shader. Without an incoming scene the
+    /// shader sees a transparent texture.
     pub fn transition(
         &mut self,
         encoder: &mut CommandEncoder,
         view: &TextureView,
-        device: &Device,
         render_pipeline: &RenderPipeline,
+        to: Option<&mut SceneType>,
     ) {
-        let texture_view = self
-            .scene_texture
+        let from_view = self
+            .from_texture
             .create_view(&wgpu::TextureViewDescriptor::default());
         self.scene
-            .render_scene(encoder, &texture_view, render_pipeline);
+            .render_scene(encoder, &from_view, render_pipeline);
 
-        self.create_bind_group(device, &texture_view);
+        let to_view = self
+            .to_texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        match to {
+            Some(scene) => scene.render_scene(encoder, &to_view, render_pipeline),
+            None => {
+                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Transition clear pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &to_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: true,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                });
+            }
+        }
 
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Transition pass"),
@@ -280,7 +896,7 @@ impl Transition {
                 view,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Load,
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
                     store: true,
                 },
             })],
@@ -300,3 +916,85 @@ impl Transition {
         render_pass.draw_indexed(0..self.plane.get_indices().len() as u32, 0, 0..1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{manifest::Manifest, renderer::engine::tests::headless};
+
+    #[test]
+    fn unknown_names_list_every_preset() {
+        let manifest = "transition = { name = \"spin\" }\n[scene.image]\npath = \"1.png\"";
+        let error = format!("{:#}", Manifest::from_toml(manifest).unwrap_err());
+        assert!(error.contains("`random`"), "{error}");
+        for kind in TransitionKind::ALL {
+            assert!(error.contains(&format!("`{}`", kind.name())), "{error}");
+        }
+    }
+
+    #[test]
+    fn presets_share_their_pipeline() {
+        let Some((mut engine, _clock)) = headless(32, 32) else {
+            return;
+        };
+        let image = concat!(env!("CARGO_MANIFEST_DIR"), "/images/1.png");
+        let scene = format!("[scene.image]\npath = {image:?}");
+        engine
+            .load_scene(&Manifest::from_toml(&scene).unwrap())
+            .unwrap();
+
+        let mut pipelines = Vec::new();
+        for name in ["dissolve", "fade", "dissolve"] {
+            let manifest = format!("transition = {{ name = {name:?} }}\n{scene}");
+            engine
+                .load_scene(&Manifest::from_toml(&manifest).unwrap())
+                .unwrap();
+            pipelines.push(engine.transition().unwrap().transition_pipeline.clone());
+        }
+        assert!(Arc::ptr_eq(&pipelines[0], &pipelines[2]));
+        assert!(!Arc::ptr_eq(&pipelines[0], &pipelines[1]));
+    }
+
+    #[test]
+    fn dissolve_speed_fades_blocks_in() {
+        let Some((mut engine, clock)) = headless(32, 32) else {
+            return;
+        };
+        let image = concat!(env!("CARGO_MANIFEST_DIR"), "/images/1.png");
+        // Scenes of a single color, the image being too small to cover a pixel
+        let scene = |color: f32, transition: &str| {
+            Manifest::from_toml(&format!(
+                "clear_color = [{color}, {color}, {color}, 1.0]\n{transition}\n\
+                 [scene.image]\npath = {image:?}\nfit = \"contain\"\nscale = 0.001"
+            ))
+            .unwrap()
+        };
+
+        let mut halfway = |speed: &str| {
+            clock.set(Duration::ZERO);
+            engine.load_scene(&scene(0.0, "")).unwrap();
+            let transition =
+                format!("transition = {{ name = \"dissolve\", duration = 2.0{speed} }}");
+            engine.load_scene(&scene(1.0, &transition)).unwrap();
+            clock.set(Duration::from_secs(1));
+            engine.update();
+            engine.render().unwrap();
+            let mut shades: Vec<u8> = engine
+                .read_frame()
+                .unwrap()
+                .pixels()
+                .map(|p| p[0])
+                .collect();
+            shades.sort();
+            shades.dedup();
+            shades
+        };
+
+        // Blocks switch at once, or all fade together when fading over the full duration
+        assert_eq!(halfway(""), [0, 255]);
+        let faded = halfway(", speed = 1.0");
+        // Half of white in linear color, as the render targets are sRGB
+        assert_eq!(faded.len(), 1);
+        assert!((186..=189).contains(&faded[0]), "{faded:?}");
+    }
+}
@@ -0,0 +1,313 @@
+use anyhow::{bail, Context};
+use naga::{
+    AddressSpace, Binding, ImageClass, ImageDimension, ResourceBinding, ScalarKind, ShaderStage,
+    TypeInner, VectorSize,
+};
+
+use crate::manifest::{Manifest, SceneManifest};
+
+use super::{animated, loader, transitions::TransitionKind, video::VideoInfo};
+
+/// Checks the parameters of an effect or transition, given as keys with their numbers or
+/// `None` where unset: set ones must be read by `owner`, listed in `accepted`, and finite.
+pub fn validate_parameters(
+    key: &str,
+    owner: &str,
+    accepted: &[&str],
+    parameters: Vec<(&'static str, Option<Vec<f32>>)>,
+) -> anyhow::Result<()> {
+    for (parameter, values) in parameters {
+        let Some(values) = values else {
+            continue;
+        };
+        if !accepted.contains(&parameter) {
+            bail!("`{key}.{parameter}`: not a parameter of {owner}");
+        }
+        if values.iter().any(|value| !value.is_finite()) {
+            bail!("`{key}.{parameter}`: must be a number");
+        }
+    }
+    Ok(())
+}
+
+/// Parses and validates a WGSL module with naga, without needing a GPU.
+pub fn validate_wgsl(label: &str, source: &str) -> anyhow::Result<naga::Module> {
+    let module = naga::front::wgsl::parse_str(source)
+        .map_err(|e| anyhow::anyhow!("{}", e.emit_to_string_with_path(source, label)))?;
+
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::empty(),
+    )
+    .validate(&module)
+    .map_err(|e| anyhow::anyhow!("{}", e.emit_to_string_with_path(source, label)))?;
+
+    Ok(module)
+}
+
+/// Checks a transition shader against the contract documented on
+/// [`TransitionShader`](super::transitions::TransitionShader): a `main_fragment` entry point
+/// reading `tex_coords` at location 0, and only the bindings the engine provides.
+pub fn validate_transition_shader(label: &str, source: &str) -> anyhow::Result<()> {
+    let module = validate_wgsl(label, source)?;
+
+    let entry_point = module
+        .entry_points
+        .iter()
+        .find(|ep| ep.name == "main_fragment" && ep.stage == ShaderStage::Fragment)
+        .ok_or_else(|| anyhow::anyhow!("{label}: missing `@fragment fn main_fragment`"))?;
+
+    let mut inputs = Vec::new();
+    for argument in &entry_point.function.arguments {
+        match (&argument.binding, &module.types[argument.ty].inner) {
+            (Some(binding), _) => inputs.push((binding.clone(), argument.ty)),
+            (None, TypeInner::Struct { members, .. }) => inputs.extend(
+                members
+                    .iter()
+                    .filter_map(|m| m.binding.clone().map(|binding| (binding, m.ty))),
+            ),
+            (None, _) => {}
+        }
+    }
+    for (binding, ty) in inputs {
+        if let Binding::Location { location, .. } = binding {
+            let is_vec2 = matches!(
+                module.types[ty].inner,
+                TypeInner::Vector {
+                    size: VectorSize::Bi,
+                    kind: ScalarKind::Float,
+                    ..
+                }
+            );
+            if location != 0 || !is_vec2 {
+                bail!(
+                    "{label}: `main_fragment` input `@location({location})` is not provided, \
+                     only `@location(0) tex_coords: vec2<f32>` is"
+                );
+            }
+        }
+    }
+
+    for (_, global) in module.global_variables.iter() {
+        let Some(ResourceBinding { group, binding }) = global.binding else {
+            continue;
+        };
+        let name = global.name.as_deref().unwrap_or("<unnamed>");
+        let inner = &module.types[global.ty].inner;
+
+        let expected = match (group, binding) {
+            (0, 0) | (0, 2) => matches!(
+                inner,
+                TypeInner::Image {
+                    dim: ImageDimension::D2,
+                    arrayed: false,
+                    class: ImageClass::Sampled {
+                        kind: ScalarKind::Float,
+                        multi: false,
+                    },
+                }
+            )
+            .then_some(())
+            .ok_or("texture_2d<f32>"),
+            (0, 1) => matches!(inner, TypeInner::Sampler { comparison: false })
+                .then_some(())
+                .ok_or("sampler"),
+            (1, 0) => check_constants(&module, global.space, inner),
+            _ => bail!(
+                "{label}: `{name}` uses `@group({group}) @binding({binding})`, which the engine \
+                 doesn't provide"
+            ),
+        };
+
+        if let Err(expected) = expected {
+            bail!(
+                "{label}: `{name}` at `@group({group}) @binding({binding})` must be `{expected}`"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The `Constants` uniform may declare any prefix of
+/// `{ progress: f32, time: f32, resolution: vec2<f32>, params: vec4<f32> }`.
+fn check_constants(
+    module: &naga::Module,
+    space: AddressSpace,
+    inner: &TypeInner,
+) -> Result<(), &'static str> {
+    const EXPECTED: &str = "var<uniform> struct { progress: f32, time: f32, \
+                            resolution: vec2<f32>, params: vec4<f32> }";
+
+    let TypeInner::Struct { members, .. } = inner else {
+        return Err(EXPECTED);
+    };
+    if space != AddressSpace::Uniform {
+        return Err(EXPECTED);
+    }
+
+    let layout = [
+        (0, None),
+        (4, None),
+        (8, Some(VectorSize::Bi)),
+        (16, Some(VectorSize::Quad)),
+    ];
+    if members.len() > layout.len() {
+        return Err(EXPECTED);
+    }
+    for (member, (offset, size)) in members.iter().zip(layout) {
+        let matches = match (&module.types[member.ty].inner, size) {
+            (TypeInner::Scalar { kind, .. }, None) => *kind == ScalarKind::Float,
+            (
+                TypeInner::Vector {
+                    kind, size: actual, ..
+                },
+                Some(size),
+            ) => *kind == ScalarKind::Float && *actual == size,
+            _ => false,
+        };
+        if !matches || member.offset != offset {
+            return Err(EXPECTED);
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks everything a manifest needs to be displayed: every asset decodes and every
+/// shader it would use compiles.
+pub fn validate_manifest(manifest: &Manifest) -> anyhow::Result<()> {
+    validate_scene(&manifest.scene, "scene")?;
+
+    validate_wgsl("shader.wgsl", include_str!("shaders/shader.wgsl"))?;
+    validate_wgsl(
+        "transition_vertex.wgsl",
+        include_str!("shaders/transition_vertex.wgsl"),
+    )?;
+    // Any preset may be picked by a `random` transition, in this scene or a nested one
+    for kind in TransitionKind::ALL {
+        validate_transition_shader(&format!("{}.wgsl", kind.name()), kind.shader_source())?;
+    }
+    if let Some(transition) = &manifest.transition {
+        transition.load().context("`transition`")?;
+    }
+    for effect in &manifest.effects {
+        let kind = effect.name;
+        validate_wgsl(&format!("{}.wgsl", kind.name()), kind.shader_source())?;
+    }
+
+    Ok(())
+}
+
+/// Decodes the assets of the scene at `key` and of the scenes nested in it.
+fn validate_scene(scene: &SceneManifest, key: &str) -> anyhow::Result<()> {
+    for (image_key, image) in scene.images(key) {
+        if let Some(effect) = &image.effect {
+            let kind = effect.name;
+            validate_wgsl(&format!("{}.wgsl", kind.name()), &kind.shader_source())
+                .with_context(|| format!("`{image_key}.effect`"))?;
+        }
+
+        let path = &image.path;
+        match scene {
+            SceneManifest::Gif(gif) => {
+                // One frame at a time, so long gifs aren't held in memory
+                let (mut count, mut bytes) = (0, 0);
+                for frame in
+                    animated::decode_frames(path).with_context(|| format!("`{image_key}.path`"))?
+                {
+                    let frame = frame.with_context(|| {
+                        format!("`{image_key}.path`: failed to decode `{}`", path.display())
+                    })?;
+                    count += 1;
+                    bytes += frame.buffer().len() as u64;
+                }
+                // As `loader::decode_animation` decides
+                let streamed = count > 1 && bytes > animated::memory_budget(gif.memory_budget);
+                if gif.reverse && streamed {
+                    bail!(
+                        "`{image_key}.reverse`: `{}` is over the memory budget, so it is \
+                         streamed and can only play forwards",
+                        path.display()
+                    );
+                }
+            }
+            SceneManifest::Video(_) => {
+                VideoInfo::probe(path).with_context(|| format!("`{image_key}.path`"))?;
+            }
+            SceneManifest::Image(_) | SceneManifest::Images(_) | SceneManifest::Parallax(_) => {
+                image::open(path).with_context(|| {
+                    format!("`{image_key}.path`: failed to decode `{}`", path.display())
+                })?;
+            }
+            // Have no images of their own, checked below
+            SceneManifest::Slideshow(_) | SceneManifest::Schedule(_) => {}
+        }
+    }
+
+    match scene {
+        SceneManifest::Slideshow(slideshow) => {
+            let slides = slideshow
+                .slides()
+                .with_context(|| format!("`{key}.slideshow`"))?;
+            for (i, path) in slides.iter().enumerate() {
+                let slide_key = match slideshow.directory {
+                    Some(_) => format!("`{key}.slideshow.directory`"),
+                    None => format!("`{key}.slideshow.paths[{i}]`"),
+                };
+                loader::decode_slide(path).context(slide_key)?;
+            }
+            if let Some(transition) = &slideshow.transition {
+                transition
+                    .load()
+                    .with_context(|| format!("`{key}.slideshow.transition`"))?;
+            }
+        }
+        SceneManifest::Schedule(schedule) => {
+            for (i, entry) in schedule.entries.iter().enumerate() {
+                validate_scene(&entry.scene, &format!("{key}.schedule.entries[{i}].scene"))?;
+            }
+            if let Some(transition) = &schedule.transition {
+                transition
+                    .load()
+                    .with_context(|| format!("`{key}.schedule.transition`"))?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::animated::tests::{gif, TempFile};
+
+    #[test]
+    fn preset_transitions_follow_the_shader_contract() {
+        for kind in TransitionKind::ALL {
+            let label = format!("{}.wgsl", kind.name());
+            if let Err(e) = validate_transition_shader(&label, kind.shader_source()) {
+                panic!("{e:#}");
+            }
+        }
+    }
+
+    #[test]
+    fn reverse_is_rejected_for_streamed_animations() {
+        // Three frames of 400x400 RGBA take about 1.8 MB decoded
+        let file = TempFile::new("reverse.gif", &gif(3, 400, 100));
+        let manifest = |budget: u32| {
+            Manifest::from_toml(&format!(
+                "[scene.gif]\npath = {:?}\nreverse = true\nmemory_budget = {budget}",
+                file.0
+            ))
+            .unwrap()
+        };
+
+        let error = validate_manifest(&manifest(1)).unwrap_err();
+        assert!(format!("{error:#}").contains("`scene.gif.reverse`"));
+        validate_manifest(&manifest(2)).unwrap();
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    time::Duration,
+};
+
+use anyhow::{bail, Context};
+
+/// Number of decoded frames buffered ahead of playback.
+const FRAME_BUFFER: usize = 4;
+
+/// Size and frame rate of a video's first video stream, as reported by `ffprobe`. The size
+/// is the stored one: rotation metadata is ignored when decoding, see [`VideoDecoder`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VideoInfo {
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: f32,
+}
+
+impl VideoInfo {
+    pub fn probe(path: &Path) -> anyhow::Result<Self> {
+        let output = Command::new("ffprobe")
+            .args(["-v", "error", "-select_streams", "v:0"])
+            .args(["-show_entries", "stream=width,height,r_frame_rate"])
+            .args(["-of", "csv=p=0"])
+            .arg(path)
+            .output()
+            .context("failed to run `ffprobe`, is ffmpeg installed?")?;
+        if !output.status.success() {
+            bail!(
+                "`ffprobe` failed on `{}`: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let fields: Vec<&str> = stdout.trim().split(',').collect();
+        let [width, height, frame_rate] = fields[..] else {
+            bail!("`{}` has no video stream", path.display());
+        };
+
+        let parse_error =
+            |what, value| format!("`{}`: `ffprobe` reported {what} `{value}`", path.display());
+        let frame_rate = match frame_rate.split_once('/') {
+            Some((num, den)) => num
+                .parse::<f32>()
+                .and_then(|num| Ok(num / den.parse::<f32>()?)),
+            None => frame_rate.parse(),
+        }
+        .with_context(|| parse_error("frame rate", frame_rate))?;
+        if width == "0" || !frame_rate.is_finite() || frame_rate <= 0.0 {
+            bail!("`{}` has no usable video stream", path.display());
+        }
+
+        Ok(VideoInfo {
+            width: width.parse().with_context(|| parse_error("width", width))?,
+            height: height
+                .parse()
+                .with_context(|| parse_error("height", height))?,
+            frame_rate,
+        })
+    }
+
+    pub fn frame_size(&self) -> usize {
+        4 * self.width as usize * self.height as usize
+    }
+}
+
+/// Raw RGBA frames piped out of an `ffmpeg` subprocess. A background thread reads them into
+/// a small bounded queue, so decoding stays a few frames ahead of playback. Frames come out
+/// as stored, without applying rotation metadata, so they match the size `ffprobe` reports.
+pub struct VideoDecoder {
+    pub path: PathBuf,
+    pub info: VideoInfo,
+    child: Child,
+    frames: Receiver<Vec<u8>>,
+}
+
+impl VideoDecoder {
+    /// Starts decoding at `start`. With `looping` the video restarts from its beginning
+    /// after the last frame, forever.
+    pub fn spawn(
+        path: &Path,
+        info: VideoInfo,
+        start: Duration,
+        looping: bool,
+    ) -> anyhow::Result<Self> {
+        let mut command = Command::new("ffmpeg");
+        command.args(["-v", "error", "-nostdin"]);
+        if looping {
+            command.args(["-stream_loop", "-1"]);
+        }
+        let mut child = command
+            .args(["-ss", &start.as_secs_f64().to_string()])
+            .arg("-noautorotate")
+            .arg("-i")
+            .arg(path)
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba", "-an", "-"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("failed to run `ffmpeg`, is it installed?")?;
+
+        let mut stdout = child.stdout.take().unwrap();
+        let (sender, frames) = mpsc::sync_channel(FRAME_BUFFER);
+        let frame_size = info.frame_size();
+        std::thread::Builder::new()
+            .name("video decoder".to_string())
+            .spawn(move || loop {
+                let mut frame = vec![0; frame_size];
+                if stdout.read_exact(&mut frame).is_err() || sender.send(frame).is_err() {
+                    break;
+                }
+            })?;
+
+        Ok(VideoDecoder {
+            path: path.to_path_buf(),
+            info,
+            child,
+            frames,
+        })
+    }
+
+    /// The next decoded frame, `Ok(None)` if it isn't ready yet and an error once the video
+    /// has ended. With `wait`, blocks until the frame is decoded instead.
+    pub fn next_frame(&self, wait: bool) -> Result<Option<Vec<u8>>, TryRecvError> {
+        if wait {
+            return self
+                .frames
+                .recv()
+                .map(Some)
+                .map_err(|_| TryRecvError::Disconnected);
+        }
+        match self.frames.try_recv() {
+            Ok(frame) => Ok(Some(frame)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Drop for VideoDecoder {
+    fn drop(&mut self) {
+        // The reader thread ends once the pipe closes or the queue is dropped
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A lossless 32x24 clip of `frames` frames at 10 fps, named after the calling test.
+    /// `None` without ffmpeg, in which case the test is skipped.
+    pub(crate) fn clip(name: &str, frames: u32) -> Option<PathBuf> {
+        let path = std::env::temp_dir().join(format!("hemera-{name}-{}.mkv", std::process::id()));
+        let status = Command::new("ffmpeg")
+            .args(["-v", "error", "-nostdin", "-y", "-f", "lavfi"])
+            .args(["-i", "testsrc=size=32x24:rate=10"])
+            .args(["-frames:v", &frames.to_string(), "-c:v", "ffv1"])
+            .arg(&path)
+            .status();
+        match status {
+            Ok(status) if status.success() => Some(path),
+            _ => {
+                eprintln!("skipped: ffmpeg can't generate a test clip");
+                None
+            }
+        }
+    }
+
+    fn count_frames(decoder: &VideoDecoder, limit: usize) -> usize {
+        let mut count = 0;
+        while count < limit {
+            match decoder.next_frame(true) {
+                Ok(Some(frame)) => {
+                    assert_eq!(frame.len(), decoder.info.frame_size());
+                    count += 1;
+                }
+                Ok(None) => unreachable!("waiting never comes back empty"),
+                Err(_) => break,
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn probe_reports_size_and_frame_rate() {
+        let Some(path) = clip("probe", 10) else {
+            return;
+        };
+        let info = VideoInfo::probe(&path).unwrap();
+        assert_eq!((info.width, info.height), (32, 24));
+        assert_eq!(info.frame_rate, 10.0);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn decodes_every_frame_then_ends() {
+        let Some(path) = clip("frames", 10) else {
+            return;
+        };
+        let info = VideoInfo::probe(&path).unwrap();
+        let decoder = VideoDecoder::spawn(&path, info, Duration::ZERO, false).unwrap();
+        assert_eq!(count_frames(&decoder, 100), 10);
+        assert!(decoder.next_frame(false).is_err());
+
+        let decoder = VideoDecoder::spawn(&path, info, Duration::from_millis(500), false).unwrap();
+        assert_eq!(count_frames(&decoder, 100), 5);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn looping_restarts_after_the_last_frame() {
+        let Some(path) = clip("loop", 10) else {
+            return;
+        };
+        let info = VideoInfo::probe(&path).unwrap();
+        let decoder = VideoDecoder::spawn(&path, info, Duration::ZERO, true).unwrap();
+        assert_eq!(count_frames(&decoder, 35), 35);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -4,100 +4,87 @@ use winit::{
     window::WindowBuilder,
 };
 
-use crate::renderer::engine::Engine;
+use hemera::{Engine, Manifest};
 
-pub async fn run() {
+/// Opens a window showing the first manifest. Space switches to the next one.
+pub async fn run(manifests: Vec<Manifest>) -> anyhow::Result<()> {
     let mut transition_count = 0;
     env_logger::init();
     let event_loop = EventLoop::new();
-    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    let window = WindowBuilder::new().build(&event_loop)?;
 
-    // let mut state = State::new(window).await;
-    let mut state = Engine::new(window).await;
-    state.load_scene(false);
-    // state.load_transition();
-
-    // event_loop.run(move |event, _, control_flow| {
-    //     match event {
-    //         Event::RedrawRequested(window_id) => {
-    //             println!("Redraw");
-    //             engine.update();
-    //             let _ = engine.render();
-    //             // state.update();
-    //             // match state.render() {
-    //             //     Ok(_) => {}
-    //             //     // Reconfigure the surface if lost
-    //             //     Err(wgpu::SurfaceError::Lost) => state.resize(state.size),
-    //             //     // The system is out of memory, we should probably quit
-    //             //     Err(wgpu::SurfaceError::OutOfMemory) => *control_flow = ControlFlow::Exit,
-    //             //     // All other errors (Outdated, Timeout) should be resolved by the next frame
-    //             //     Err(e) => eprintln!("{:?}", e),
-    //             // }
-    //         }
-    //         Event::MainEventsCleared => {
-    //             // state.window().request_redraw();
-    //         }
-    //         _ => {}
-    //     }
-    // });
+    let mut state = Engine::new(window).await?;
+    state.load_scene(&manifests[0])?;
 
     event_loop.run(move |event, _, control_flow| {
         match event {
             Event::WindowEvent {
                 ref event,
                 window_id: _,
-            } => {
-                // UPDATED!
-                match event {
-                    WindowEvent::CloseRequested
-                    | WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Pressed,
-                                virtual_keycode: Some(VirtualKeyCode::Escape),
-                                ..
-                            },
-                        ..
-                    } => *control_flow = ControlFlow::Exit,
-                    WindowEvent::Resized(_physical_size) => {
-                        // state.resize(*physical_size);
-                    }
-                    WindowEvent::ScaleFactorChanged { .. } => {
-                        // state.resize(**new_inner_size);
-                    }
-                    WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Pressed,
-                                virtual_keycode: Some(VirtualKeyCode::Space),
-                                ..
-                            },
-                        ..
-                    } => {
-                        transition_count += 1;
-                        println!("{transition_count}");
+            } => match event {
+                WindowEvent::CloseRequested
+                | WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::Escape),
+                            ..
+                        },
+                    ..
+                } => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(physical_size) => {
+                    state.resize(*physical_size);
+                }
+                WindowEvent::CursorMoved { position, .. } => {
+                    state.set_cursor_position(Some(*position));
+                }
+                WindowEvent::CursorLeft { .. } => {
+                    state.set_cursor_position(None);
+                }
+                WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    new_inner_size,
+                } => {
+                    state.set_scale_factor(*scale_factor, **new_inner_size);
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::Space),
+                            ..
+                        },
+                    ..
+                } => {
+                    transition_count += 1;
+                    let index = transition_count % manifests.len();
+                    log::debug!("switching to manifest {index}");
 
-                        state.load_transition();
-                        state.load_scene(transition_count % 2 == 1);
+                    let manifest = &manifests[index];
+                    if let Err(e) = state.load_scene(manifest) {
+                        eprintln!("{e:#}");
                     }
-                    _ => {}
                 }
-            }
+                _ => {}
+            },
             Event::RedrawRequested(_window_id) => {
                 state.update();
                 match state.render() {
                     Ok(_) => {}
-                    // Reconfigure the surface if lost
-                    Err(wgpu::SurfaceError::Lost) => {}
+                    // Reconfigure the surface if lost or no longer matching the window
+                    Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                        state.resize(state.size())
+                    }
                     // The system is out of memory, we should probably quit
                     Err(wgpu::SurfaceError::OutOfMemory) => *control_flow = ControlFlow::Exit,
-                    // All other errors (Outdated, Timeout) should be resolved by the next frame
+                    // Timeouts should be resolved by the next frame
                     Err(e) => eprintln!("{:?}", e),
                 }
             }
             Event::MainEventsCleared => {
-                // println!("main event cleared");
-                state.window.request_redraw();
+                if let Some(window) = state.window() {
+                    window.request_redraw();
+                }
             }
             _ => {}
         }
@@ -0,0 +1,18 @@
+use hemera::{Engine, ManualClock};
+
+/// A headless engine on a manual clock. Without a graphics adapter the test fails, unless
+/// `HEMERA_SKIP_GPU_TESTS` is set and this returns `None` to skip it.
+pub fn engine(width: u32, height: u32) -> Option<(Engine, ManualClock)> {
+    match pollster::block_on(Engine::new_headless(width, height)) {
+        Ok(mut engine) => {
+            let clock = ManualClock::new();
+            engine.set_clock(clock.clone());
+            Some((engine, clock))
+        }
+        Err(_) if std::env::var_os("HEMERA_SKIP_GPU_TESTS").is_some() => {
+            eprintln!("skipped: no graphics adapter");
+            None
+        }
+        Err(e) => panic!("{e:#}, set HEMERA_SKIP_GPU_TESTS to skip tests needing one"),
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use hemera::{Engine, Manifest, ManualClock};
+use image::RgbaImage;
+
+mod common;
+
+const IMAGE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/images/1.png");
+
+fn render_at(engine: &mut Engine, clock: &ManualClock, time: Duration) -> RgbaImage {
+    clock.set(time);
+    engine.update();
+    engine.render().unwrap();
+    engine.read_frame().unwrap()
+}
+
+#[test]
+fn frames_depend_only_on_the_clock() {
+    let manifest = Manifest::from_toml(&format!(
+        r#"
+        effects = [{{ name = "film_grain" }}, {{ name = "vignette" }}]
+
+        [[scene.images]]
+        path = {IMAGE:?}
+        effect = {{ name = "waves", amplitude = 0.05 }}
+
+        [[scene.images]]
+        path = {IMAGE:?}
+        fit = "contain"
+        scale = 0.5
+        blend = "screen"
+
+        [scene.images.animation]
+        repeat = "ping_pong"
+        keyframes = [
+            {{ time = 0.0, rotation = 0.0, opacity = 1.0 }},
+            {{ time = 0.7, rotation = 90.0, opacity = 0.3, easing = "ease_in_out" }},
+        ]
+        "#
+    ))
+    .unwrap();
+    let times = [0, 16, 350, 700, 1234, 5000].map(Duration::from_millis);
+
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        let Some((mut engine, clock)) = common::engine(64, 48) else {
+            return;
+        };
+        engine.load_scene(&manifest).unwrap();
+        let frames: Vec<RgbaImage> = times
+            .iter()
+            .map(|time| render_at(&mut engine, &clock, *time))
+            .collect();
+        runs.push(frames);
+    }
+    for (time, (first, second)) in times.iter().zip(runs[0].iter().zip(&runs[1])) {
+        assert!(first == second, "frames at {time:?} differ between runs");
+    }
+    // Frames at different times do differ
+    assert!(runs[0][1] != runs[0][2]);
+}
+
+fn to_linear(value: u8) -> f32 {
+    let c = value as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn to_srgb(c: f32) -> u8 {
+    let c = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+#[test]
+fn fade_halfway_mixes_both_scenes_equally() {
+    let Some((mut engine, clock)) = common::engine(64, 48) else {
+        return;
+    };
+    let from = Manifest::from_toml(&format!(
+        "[scene.image]\npath = {IMAGE:?}\nfit = \"contain\"\nscale = 0.5"
+    ))
+    .unwrap();
+    let to = Manifest::from_toml(&format!(
+        r#"
+        clear_color = [0.8, 0.1, 0.4, 1.0]
+
+        [transition]
+        name = "fade"
+        duration = 2.0
+        easing = "linear"
+
+        [scene.image]
+        path = {IMAGE:?}
+        fit = "cover"
+        rotation = 45
+        "#
+    ))
+    .unwrap();
+
+    engine.load_scene(&from).unwrap();
+    let from_frame = render_at(&mut engine, &clock, Duration::ZERO);
+    engine.load_scene(&to).unwrap();
+    let halfway = render_at(&mut engine, &clock, Duration::from_secs(1));
+    let to_frame = render_at(&mut engine, &clock, Duration::from_secs(2));
+    assert!(!engine.is_transitioning());
+
+    // The textures are sRGB, so the shader mixes linear colors
+    for ((from, to), mixed) in from_frame
+        .pixels()
+        .zip(to_frame.pixels())
+        .zip(halfway.pixels())
+    {
+        for c in 0..3 {
+            let expected = to_srgb((to_linear(from[c]) + to_linear(to[c])) / 2.0);
+            assert!(
+                expected.abs_diff(mixed[c]) <= 2,
+                "expected {expected}, got {} mixing {from:?} and {to:?}",
+                mixed[c]
+            );
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use hemera::{
+    Clock, Engine, EngineEvent, InterruptPolicy, Manifest, ManualClock, SceneType, TransitionKind,
+    TransitionSettings,
+};
+
+mod common;
+
+const IMAGE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/images/1.png");
+
+fn image_manifest(transition: &str) -> Manifest {
+    Manifest::from_toml(&format!(
+        "{transition}\n[scene.image]\npath = {IMAGE:?}\nfit = \"contain\""
+    ))
+    .unwrap()
+}
+
+#[test]
+fn renders_a_manifest_headless() {
+    let Some((mut engine, _clock)) = common::engine(64, 48) else {
+        return;
+    };
+    assert!(engine.window().is_none());
+    assert_eq!(engine.size(), winit::dpi::PhysicalSize::new(64, 48));
+
+    engine.load_scene(&image_manifest("")).unwrap();
+    assert!(matches!(engine.scene_mut(), Some(SceneType::Image(_))));
+    assert!(engine.update().is_empty());
+    engine.render().unwrap();
+
+    let frame = engine.read_frame().unwrap();
+    assert_eq!(frame.dimensions(), (64, 48));
+}
+
+#[test]
+fn transitions_report_when_they_finish() {
+    let Some((mut engine, clock)) = common::engine(64, 48) else {
+        return;
+    };
+    engine.set_transition_quality(0.5);
+    engine.load_scene(&image_manifest("")).unwrap();
+    engine
+        .load_scene(&image_manifest(
+            "[transition]\nname = \"fade\"\nduration = 1.0",
+        ))
+        .unwrap();
+    assert!(engine.is_transitioning());
+
+    clock.set(Duration::from_millis(500));
+    assert!(engine.update().is_empty());
+    let progress = engine.transition().unwrap().progress(clock.now());
+    assert!((progress - 0.5).abs() < 1e-6);
+    engine.render().unwrap();
+
+    clock.set(Duration::from_secs(1));
+    assert_eq!(engine.update(), [EngineEvent::TransitionFinished]);
+    assert!(!engine.is_transitioning());
+}
+
+#[test]
+fn scenes_can_be_built_and_switched_separately() {
+    let Some((mut engine, clock)) = common::engine(64, 48) else {
+        return;
+    };
+    engine.set_interrupt_policy(InterruptPolicy::Chain);
+    engine.load_scene(&image_manifest("")).unwrap();
+
+    let spec = TransitionSettings::new(TransitionKind::Wipe)
+        .load()
+        .unwrap();
+    for _ in 0..2 {
+        let scene = engine.build_scene(&image_manifest("")).unwrap();
+        engine.transition_to(scene, spec.clone());
+    }
+
+    // The second transition waits for the first
+    let mut finished = 0;
+    for second in 1..=4 {
+        clock.set(Duration::from_secs(second));
+        finished += engine
+            .update()
+            .iter()
+            .filter(|event| **event == EngineEvent::TransitionFinished)
+            .count();
+        engine.render().unwrap();
+    }
+    assert_eq!(finished, 2);
+    assert!(!engine.is_transitioning());
+}
+
+#[test]
+fn resizing_renders_like_starting_at_the_new_size() {
+    let Some((mut resized, _clock)) = common::engine(64, 48) else {
+        return;
+    };
+    let manifest = Manifest::from_toml(&format!(
+        "[scene.image]\npath = {IMAGE:?}\nfit = \"contain\"\nrotation = 20"
+    ))
+    .unwrap();
+    resized.load_scene(&manifest).unwrap();
+    resized.update();
+    resized.render().unwrap();
+    resized.resize(winit::dpi::PhysicalSize::new(32, 48));
+    resized.update();
+    resized.render().unwrap();
+    let resized_frame = resized.read_frame().unwrap();
+    drop(resized);
+
+    let mut fresh = pollster::block_on(Engine::new_headless(32, 48)).unwrap();
+    fresh.set_clock(ManualClock::new());
+    fresh.load_scene(&manifest).unwrap();
+    fresh.update();
+    fresh.render().unwrap();
+    assert!(resized_frame == fresh.read_frame().unwrap());
+}
@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use hemera::Manifest;
+
+mod common;
+
+/// Largest difference allowed in any channel of any pixel, for rounding that differs between
+/// GPUs and drivers.
+const TOLERANCE: u8 = 3;
+
+/// Renders `manifest` headless and compares the frame with `tests/golden/{name}.png`. Set
+/// `HEMERA_UPDATE_GOLDEN` to write the frame there instead.
+fn assert_golden(name: &str, manifest: &str) {
+    let Some((mut engine, _clock)) = common::engine(64, 48) else {
+        return;
+    };
+    let manifest = Manifest::from_toml(
+        &manifest.replace("$IMAGES", concat!(env!("CARGO_MANIFEST_DIR"), "/images")),
+    )
+    .unwrap();
+    engine.load_scene(&manifest).unwrap();
+    engine.update();
+    engine.render().unwrap();
+    let frame = engine.read_frame().unwrap();
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"));
+    if std::env::var_os("HEMERA_UPDATE_GOLDEN").is_some() {
+        frame.save(&path).unwrap();
+        return;
+    }
+    let golden = image::open(&path).unwrap().into_rgba8();
+    assert_eq!(frame.dimensions(), golden.dimensions());
+
+    let (position, difference) = frame
+        .enumerate_pixels()
+        .zip(golden.pixels())
+        .flat_map(|((x, y, actual), expected)| {
+            (0..4).map(move |c| ((x, y), actual[c].abs_diff(expected[c])))
+        })
+        .max_by_key(|(_, difference)| *difference)
+        .unwrap();
+    assert!(
+        difference <= TOLERANCE,
+        "`{name}` differs from {} by {difference} at {position:?}",
+        path.display()
+    );
+}
+
+#[test]
+fn image_placement() {
+    assert_golden(
+        "image_placement",
+        r#"
+        clear_color = [0.1, 0.2, 0.3, 1.0]
+
+        [[scene.images]]
+        path = "$IMAGES/1.png"
+        fit = "contain"
+
+        [[scene.images]]
+        path = "$IMAGES/1.png"
+        fit = "contain"
+        scale = 0.5
+        offset = [0.4, -0.3]
+        rotation = 30
+        opacity = 0.6
+        tint = [1.0, 0.5, 0.5]
+        z_index = 1
+        "#,
+    );
+}
+
+#[test]
+fn blend_and_effect() {
+    assert_golden(
+        "blend_and_effect",
+        r#"
+        clear_color = [0.5, 0.5, 0.5, 1.0]
+
+        [[scene.images]]
+        path = "$IMAGES/1.png"
+        fit = "cover"
+        effect = { name = "desaturate", amount = 1.0 }
+
+        [[scene.images]]
+        path = "$IMAGES/1.png"
+        fit = "contain"
+        scale = 0.6
+        blend = "multiply"
+        "#,
+    );
+}